@@ -0,0 +1,18 @@
+pub const DAPP_AUTHORITY_SEED: &[u8; 14] = b"dapp_authority";
+
+// declares the `Authority` account each spoke signs xcall requests with, it has to be
+// expanded inside the program so the account is owned by that program
+#[macro_export]
+macro_rules! dapp_authority {
+    () => {
+        #[::anchor_lang::account]
+        pub struct Authority {
+            pub bump: u8,
+        }
+
+        impl Authority {
+            pub const SEED_PREFIX: &'static [u8; 14] = $crate::authority::DAPP_AUTHORITY_SEED;
+            pub const MAX_SPACE: usize = 8 + 1;
+        }
+    };
+}
@@ -0,0 +1,114 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{instruction::Instruction, program::invoke},
+};
+use rlp::{DecoderError, Encodable, Rlp, RlpStream};
+use std::str::FromStr;
+
+use crate::errors::SpokeError;
+
+// optional payload of `xCrossTransfer`, executed against `program` once the recipient is minted
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CallData {
+    pub program: Pubkey,
+    pub accounts: Vec<CallAccount>,
+    pub data: Vec<u8>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CallAccount {
+    pub pubkey: Pubkey,
+    pub is_writable: bool,
+}
+
+impl Encodable for CallAccount {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(2);
+        s.append(&self.pubkey.to_string());
+        s.append(&self.is_writable);
+    }
+}
+
+impl Encodable for CallData {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(3);
+        s.append(&self.program.to_string());
+        s.append_list(&self.accounts);
+        s.append(&self.data);
+    }
+}
+
+impl CallData {
+    pub fn create(program: Pubkey, accounts: Vec<CallAccount>, data: Vec<u8>) -> Self {
+        Self {
+            program,
+            accounts,
+            data,
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        rlp::encode(&self.clone()).to_vec()
+    }
+}
+
+pub fn decode_call_data(data: &[u8]) -> Result<CallData> {
+    decode_call_data_rlp(data).map_err(|err| err.into())
+}
+
+fn decode_call_data_rlp(data: &[u8]) -> std::result::Result<CallData, SpokeError> {
+    let rlp = Rlp::new(data);
+    if !rlp.is_list() {
+        return Err(DecoderError::RlpExpectedToBeList.into());
+    }
+    if rlp.item_count()? != 3 {
+        return Err(DecoderError::RlpInvalidLength.into());
+    }
+    let program: String = rlp.val_at(0)?;
+    let mut accounts = Vec::new();
+    for account in rlp.at(1)?.iter() {
+        if account.item_count()? != 2 {
+            return Err(DecoderError::RlpInvalidLength.into());
+        }
+        let pubkey: String = account.val_at(0)?;
+        accounts.push(CallAccount {
+            pubkey: Pubkey::from_str(&pubkey).map_err(|_| SpokeError::NotAnAddress)?,
+            is_writable: account.val_at(1)?,
+        });
+    }
+    let data: Vec<u8> = rlp.at(2)?.data()?.to_vec();
+
+    Ok(CallData {
+        program: Pubkey::from_str(&program).map_err(|_| SpokeError::NotAnAddress)?,
+        accounts,
+        data,
+    })
+}
+
+// remaining accounts are the call accounts followed by the target program, forwarded without
+// signer privileges so the target can never act as xcall or the relayer
+pub fn execute_call<'info>(call: &CallData, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+    require!(
+        remaining_accounts.len() == call.accounts.len() + 1,
+        SpokeError::InvalidCallAccounts
+    );
+    let (program, accounts) = remaining_accounts.split_last().unwrap();
+    require!(program.key() == call.program, SpokeError::InvalidCallAccounts);
+
+    let mut metas = Vec::with_capacity(call.accounts.len());
+    for (account, info) in call.accounts.iter().zip(accounts) {
+        require!(info.key() == account.pubkey, SpokeError::InvalidCallAccounts);
+        metas.push(if account.is_writable {
+            AccountMeta::new(account.pubkey, false)
+        } else {
+            AccountMeta::new_readonly(account.pubkey, false)
+        });
+    }
+    let ix = Instruction {
+        program_id: call.program,
+        accounts: metas,
+        data: call.data.clone(),
+    };
+    invoke(&ix, remaining_accounts)?;
+    Ok(())
+}
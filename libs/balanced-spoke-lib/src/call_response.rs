@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+use xcall_lib::xcall_dapp_type::HandleCallMessageResponse;
+
+// failure policy of `handle_call_message` in the spoke programs:
+// - permanent errors mean the message can never execute (sender, protocols, payload, method).
+//   They are answered with `success: false` and a "<error code>:<error name>" message so xcall
+//   completes the request and a rollback reaches the hub. Programs raise them before any transfer
+//   or mint, so a failed response never leaves partial state behind.
+// - every other error fails the instruction, nothing is committed and the request stays with
+//   xcall for the relayer to execute again.
+pub fn call_response(
+    result: Result<()>,
+    is_permanent_failure: fn(u32) -> bool,
+) -> Result<HandleCallMessageResponse> {
+    match result {
+        Ok(()) => Ok(HandleCallMessageResponse {
+            success: true,
+            message: "Success".to_owned(),
+        }),
+        Err(Error::AnchorError(error)) if is_permanent_failure(error.error_code_number) => {
+            Ok(HandleCallMessageResponse {
+                success: false,
+                message: format!("{}:{}", error.error_code_number, error.error_name),
+            })
+        }
+        Err(error) => Err(error),
+    }
+}
@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+use rlp::{DecoderError, Rlp};
+
+use crate::{
+    errors::SpokeError,
+    messages::{
+        cross_transfer::{CrossTransferMsg, CROSS_TRANSFER},
+        cross_transfer_revert::{CrossTransferRevert, CROSS_TRANSFER_REVERT},
+    },
+};
+
+pub fn decode_method(data: &[u8]) -> Result<String> {
+    let rlp = Rlp::new(data);
+
+    if !rlp.is_list() {
+        return Err(SpokeError::from(DecoderError::RlpExpectedToBeList).into());
+    }
+
+    let method: String = rlp.val_at(0).map_err(SpokeError::from)?;
+    Ok(method)
+}
+
+pub fn decode_cross_transfer(data: &[u8]) -> Result<CrossTransferMsg> {
+    decode_cross_transfer_rlp(data).map_err(|err| SpokeError::from(err).into())
+}
+
+pub fn decode_cross_transfer_revert(data: &[u8]) -> Result<CrossTransferRevert> {
+    decode_cross_transfer_revert_rlp(data).map_err(|err| SpokeError::from(err).into())
+}
+
+fn decode_cross_transfer_rlp(data: &[u8]) -> std::result::Result<CrossTransferMsg, DecoderError> {
+    let rlp = Rlp::new(data);
+    if !rlp.is_list() {
+        return Err(DecoderError::RlpExpectedToBeList);
+    }
+    let method: String = rlp.val_at(0)?;
+    if method != CROSS_TRANSFER {
+        return Err(DecoderError::RlpInvalidLength);
+    }
+
+    if rlp.item_count()? != 5 {
+        return Err(DecoderError::RlpInvalidLength);
+    }
+    let from: String = rlp.val_at(1)?;
+    let to: String = rlp.val_at(2)?;
+    let value: u128 = rlp.val_at(3)?;
+    let data: Vec<u8> = rlp.at(4)?.data()?.to_vec();
+    Ok(CrossTransferMsg {
+        from,
+        to,
+        value,
+        data,
+    })
+}
+
+// revert messages encoded before the destination network was tracked carry 3 items, the
+// 5th item is only present for spokes that manage more than one mint
+fn decode_cross_transfer_revert_rlp(
+    data: &[u8],
+) -> std::result::Result<CrossTransferRevert, DecoderError> {
+    let rlp = Rlp::new(data);
+    if !rlp.is_list() {
+        return Err(DecoderError::RlpExpectedToBeList);
+    }
+    let item_count = rlp.item_count()?;
+    if !(3..=5).contains(&item_count) {
+        return Err(DecoderError::RlpInvalidLength);
+    }
+    let method: String = rlp.val_at(0)?;
+    if method != CROSS_TRANSFER_REVERT {
+        return Err(DecoderError::RlpInvalidLength);
+    }
+    let account: String = rlp.val_at(1)?;
+    let amount: u128 = rlp.val_at(2)?;
+    let to_nid: String = if item_count >= 4 {
+        rlp.val_at(3)?
+    } else {
+        String::new()
+    };
+    let token: String = if item_count == 5 {
+        rlp.val_at(4)?
+    } else {
+        String::new()
+    };
+
+    Ok(CrossTransferRevert {
+        account,
+        amount,
+        to_nid,
+        token,
+    })
+}
@@ -0,0 +1,209 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{instruction::Instruction, native_token::LAMPORTS_PER_SOL, program::invoke},
+    system_program,
+};
+
+use crate::errors::SpokeError;
+
+pub const FEE_POOL_SEED: &'static [u8; 8] = b"fee_pool";
+
+// size of an spl token account
+pub const TOKEN_ACCOUNT_LEN: usize = 165;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum TokenAccountCreationFeeMode {
+    Disabled,
+    Fixed,
+    // fee follows the rent of a token account, converted with `tokens_per_sol`
+    RentPegged,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum RelayerReimbursement {
+    // creation fee is paid in tokens to the admin token account
+    Admin,
+    // creation fee is paid in tokens to a token account owned by the executing relayer
+    SignerTokens,
+    // token account rent is paid back to the executing relayer from the fee pool
+    SignerLamports,
+}
+
+pub fn creation_fee(
+    fee_mode: TokenAccountCreationFeeMode,
+    token_account_creation_fee: u64,
+    tokens_per_sol: u64,
+) -> Result<u64> {
+    match fee_mode {
+        TokenAccountCreationFeeMode::Disabled => Ok(0),
+        TokenAccountCreationFeeMode::Fixed => Ok(token_account_creation_fee),
+        TokenAccountCreationFeeMode::RentPegged => {
+            let rent = Rent::get()?.minimum_balance(TOKEN_ACCOUNT_LEN);
+            let fee = (rent as u128 * tokens_per_sol as u128) / LAMPORTS_PER_SOL as u128;
+            Ok(fee as u64)
+        }
+    }
+}
+
+// declares the `TokenAccountCreationFee` account, fields passed in come before the fee settings.
+// It has to be expanded inside the program so the account is owned by that program
+#[macro_export]
+macro_rules! token_account_creation_fee {
+    ($($field:ident: $ty:ty),* $(,)?) => {
+        #[::anchor_lang::account]
+        #[derive(::anchor_lang::InitSpace)]
+        pub struct TokenAccountCreationFee {
+            $(pub $field: $ty,)*
+            pub token_account_creation_fee: u64,
+            pub fee_mode: $crate::creation_fee::TokenAccountCreationFeeMode,
+            pub tokens_per_sol: u64,
+        }
+
+        impl TokenAccountCreationFee {
+            pub fn fee(&self) -> ::anchor_lang::Result<u64> {
+                $crate::creation_fee::creation_fee(
+                    self.fee_mode,
+                    self.token_account_creation_fee,
+                    self.tokens_per_sol,
+                )
+            }
+        }
+    };
+}
+
+// accounts needed to create the associated token account of a message recipient
+pub struct RecipientTokenAccount<'info> {
+    pub payer: AccountInfo<'info>,
+    pub token_account: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub associated_token_program: AccountInfo<'info>,
+}
+
+// creates the recipient token account when it does not exist yet and returns whether it did,
+// the creation fee is only charged for token accounts the relayer paid for
+pub fn create_recipient_token_account(accounts: RecipientTokenAccount) -> Result<bool> {
+    if !accounts.token_account.data_is_empty() {
+        return Ok(false);
+    }
+    let ix = Instruction {
+        program_id: accounts.associated_token_program.key(),
+        accounts: vec![
+            AccountMeta::new(accounts.payer.key(), true),
+            AccountMeta::new(accounts.token_account.key(), false),
+            AccountMeta::new_readonly(accounts.authority.key(), false),
+            AccountMeta::new_readonly(accounts.mint.key(), false),
+            AccountMeta::new_readonly(accounts.system_program.key(), false),
+            AccountMeta::new_readonly(accounts.token_program.key(), false),
+        ],
+        // `Create` of the associated token account program
+        data: vec![0],
+    };
+    invoke(
+        &ix,
+        &[
+            accounts.payer,
+            accounts.token_account,
+            accounts.authority,
+            accounts.mint,
+            accounts.system_program,
+            accounts.token_program,
+            accounts.associated_token_program,
+        ],
+    )?;
+    Ok(true)
+}
+
+// accounts and settings `token_account_creation_fee` reads from the executing message
+pub struct CreationFeeAccounts<'info> {
+    pub signer: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub fee_pool: Option<AccountInfo<'info>>,
+    pub fee_pool_bump: Option<u8>,
+    // owner of the token account the fee is paid into
+    pub fee_token_account_owner: Option<Pubkey>,
+    // fee configured in the `TokenAccountCreationFee` account, if one was passed
+    pub configured_fee: Option<u64>,
+}
+
+// fee paid out of `amount` when the relayer created the recipient token account in this
+// instruction, revert paths only pay it when the program is configured to
+pub fn token_account_creation_fee(
+    accounts: CreationFeeAccounts,
+    relayer_reimbursement: RelayerReimbursement,
+    admin: Pubkey,
+    recipient_created: bool,
+    amount: u64,
+    is_revert: bool,
+    charge_on_revert: bool,
+) -> Result<u64> {
+    if !recipient_created || (is_revert && !charge_on_revert) {
+        return Ok(0);
+    }
+    if relayer_reimbursement == RelayerReimbursement::SignerLamports {
+        let fee_pool = accounts.fee_pool.ok_or(SpokeError::FeePoolIsRequired)?;
+        // an underfunded pool reimburses what it can rather than blocking the message
+        let rent = Rent::get()?.minimum_balance(TOKEN_ACCOUNT_LEN);
+        let reimbursement = rent.min(fee_pool_balance(&fee_pool)?);
+        if reimbursement > 0 {
+            transfer_from_fee_pool(
+                fee_pool,
+                accounts.signer,
+                accounts.system_program,
+                reimbursement,
+                accounts.fee_pool_bump.ok_or(SpokeError::FeePoolIsRequired)?,
+            )?;
+        }
+        return Ok(0);
+    }
+    let fee = accounts
+        .configured_fee
+        .ok_or(SpokeError::TokenAccountCreationFeeRequired)?;
+    // a refund too small to pay the fee is returned in full so the revert always goes through
+    if fee == 0 || (is_revert && amount < fee) {
+        return Ok(0);
+    }
+    // `query_handle_call_message_accounts` can not know the relayer and returns the admin token
+    // account, relayers collect the fee in `SignerTokens` mode by passing their own instead
+    let fee_recipient = accounts
+        .fee_token_account_owner
+        .ok_or(SpokeError::InvalidFeeRecipient)?;
+    require!(
+        fee_recipient == admin
+            || (relayer_reimbursement == RelayerReimbursement::SignerTokens
+                && fee_recipient == accounts.signer.key()),
+        SpokeError::InvalidFeeRecipient
+    );
+    require!(amount >= fee, SpokeError::MintAmountLessThanTokenCreationFee);
+    Ok(fee)
+}
+
+// lamports held by the fee pool minus the rent exempt reserve
+pub fn fee_pool_balance(fee_pool: &AccountInfo) -> Result<u64> {
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(0);
+    Ok(fee_pool.lamports().saturating_sub(rent_exempt_minimum))
+}
+
+pub fn transfer_from_fee_pool<'info>(
+    fee_pool: AccountInfo<'info>,
+    recipient: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    amount: u64,
+    bump: u8,
+) -> Result<()> {
+    let seeds: &[&[u8]; 2] = &[FEE_POOL_SEED, &[bump]];
+    let signer = &[&seeds[..]];
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            system_program,
+            system_program::Transfer {
+                from: fee_pool,
+                to: recipient,
+            },
+            signer,
+        ),
+        amount,
+    )
+}
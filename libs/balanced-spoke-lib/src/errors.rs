@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+use rlp::DecoderError;
+
+// offset keeps these codes clear of the 6000 range used by the program error enums
+#[error_code(offset = 9000)]
+pub enum SpokeError {
+    #[msg("Method Decode Error")]
+    DecoderError,
+    #[msg("Invalid network address")]
+    InvalidNetworkAddress,
+    #[msg("Xcall accounts are required")]
+    XcallAccountsRequired,
+    #[msg("Invalid xcall proxy request")]
+    InvalidProxyRequest,
+    #[msg("Invalid xcall config")]
+    InvalidXcallConfig,
+    #[msg("Invalid xcall admin")]
+    InvalidXcallAdmin,
+    #[msg("Message already executed")]
+    MessageAlreadyExecuted,
+    #[msg("Not an address")]
+    NotAnAddress,
+    #[msg("Invalid call accounts")]
+    InvalidCallAccounts,
+    #[msg("Fee pool is required")]
+    FeePoolIsRequired,
+    #[msg("Token account creation fee is required")]
+    TokenAccountCreationFeeRequired,
+    #[msg("Invalid fee recipient")]
+    InvalidFeeRecipient,
+    #[msg("Mint amount less than token creation fee")]
+    MintAmountLessThanTokenCreationFee,
+    #[msg("Invalid rollback request")]
+    InvalidRollbackRequest,
+    #[msg("Message is not executed by a top level xcall instruction")]
+    InvalidXcallRequest,
+    #[msg("Invalid xcall program")]
+    InvalidXcallProgram,
+    #[msg("Only the recipient of the message or the admin can skip its call data")]
+    NotMessageRecipient,
+    #[msg("The xcall requests of the message record are not closed")]
+    MessageRequestNotClosed,
+}
+
+impl From<DecoderError> for SpokeError {
+    fn from(_err: DecoderError) -> Self {
+        SpokeError::DecoderError
+    }
+}
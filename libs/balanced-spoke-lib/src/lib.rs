@@ -0,0 +1,15 @@
+pub mod authority;
+pub mod call_data;
+pub mod call_response;
+pub mod codec;
+pub mod creation_fee;
+pub mod errors;
+pub mod message_record;
+pub mod messages;
+pub mod migration;
+pub mod network;
+pub mod params;
+pub mod token_spoke;
+pub mod user_rollback;
+pub mod xcall_cpi;
+pub mod xcall_pda;
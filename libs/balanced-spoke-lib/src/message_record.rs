@@ -0,0 +1,109 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{hash::hashv, sysvar::instructions::get_instruction_relative},
+};
+
+use crate::{errors::SpokeError, migration::grow_account};
+
+pub const MESSAGE_RECORD_SEED: &'static [u8; 14] = b"message_record";
+
+const EXECUTE_CALL_DISCRIMINATOR: [u8; 8] = [62, 92, 205, 53, 148, 71, 217, 96];
+const EXECUTE_ROLLBACK_DISCRIMINATOR: [u8; 8] = [10, 217, 27, 106, 53, 35, 82, 17];
+// position of the proxy request or rollback account in both xcall instructions
+const REQUEST_ACCOUNT_INDEX: usize = 4;
+
+// the query only sees the sender and payload, so records are found by them and list the xcall
+// requests that executed the payload
+pub fn message_hash(from: &str, data: &[u8]) -> [u8; 32] {
+    hashv(&[&(from.len() as u32).to_be_bytes(), from.as_bytes(), data]).to_bytes()
+}
+
+pub fn message_record_address(program_id: &Pubkey, from: &str, data: &[u8]) -> Pubkey {
+    Pubkey::find_program_address(&[MESSAGE_RECORD_SEED, &message_hash(from, data)], program_id).0
+}
+
+pub fn message_record_space(requests: usize) -> usize {
+    8 + 1 + 4 + 32 * requests
+}
+
+// declares the `MessageRecord` account, it has to be expanded inside the program so the account is
+// owned by that program
+#[macro_export]
+macro_rules! message_record {
+    () => {
+        #[::anchor_lang::account]
+        #[derive(::anchor_lang::InitSpace)]
+        pub struct MessageRecord {
+            // set by `skip_call_data` of the token spokes, the transfer is then minted without
+            // executing its call data
+            pub call_skipped: bool,
+            #[max_len(1)]
+            pub requests: Vec<::anchor_lang::prelude::Pubkey>,
+        }
+    };
+}
+
+// declares the admin `CloseMessageRecord` accounts, the rent of the record goes back to the admin
+#[macro_export]
+macro_rules! close_message_record {
+    ($error:ident :: $only_admin:ident) => {
+        #[derive(::anchor_lang::Accounts)]
+        pub struct CloseMessageRecord<'info> {
+            #[account(seeds = [STATE_SEED], bump)]
+            pub state: Account<'info, State>,
+            #[account(mut, address=state.admin @$error::$only_admin)]
+            pub admin: Signer<'info>,
+            #[account(mut, close = admin)]
+            pub message_record: Account<'info, MessageRecord>,
+        }
+    };
+}
+
+// xcall does not hand the request to dapps, it is read from the top level xcall instruction: the
+// proxy request of `execute_call` or the rollback account of `execute_rollback`. Both are checked
+// by xcall against the request they execute. The instructions sysvar only lists top level
+// instructions, so a message executed by a program that calls xcall through CPI is rejected
+pub fn xcall_request(instructions_sysvar: &AccountInfo, xcall: &Pubkey) -> Result<Pubkey> {
+    let ix = get_instruction_relative(0, instructions_sysvar)?;
+    require_keys_eq!(ix.program_id, *xcall, SpokeError::InvalidXcallRequest);
+    require!(
+        ix.data.len() >= 8
+            && (ix.data[..8] == EXECUTE_CALL_DISCRIMINATOR
+                || ix.data[..8] == EXECUTE_ROLLBACK_DISCRIMINATOR),
+        SpokeError::InvalidXcallRequest
+    );
+    ix.accounts
+        .get(REQUEST_ACCOUNT_INDEX)
+        .map(|meta| meta.pubkey)
+        .ok_or(SpokeError::InvalidXcallRequest.into())
+}
+
+// adds the executing request to the record of its payload, a request is only executed once while
+// identical payloads of separate requests are all accepted. A record is only closed once every
+// request it lists is gone, see `require_requests_closed`
+pub fn record_request<'info>(
+    requests: &mut Vec<Pubkey>,
+    record: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    instructions_sysvar: &AccountInfo<'info>,
+    xcall: &Pubkey,
+) -> Result<()> {
+    let request = xcall_request(instructions_sysvar, xcall)?;
+    require!(!requests.contains(&request), SpokeError::MessageAlreadyExecuted);
+    grow_account(record, payer, system_program, message_record_space(requests.len() + 1))?;
+    requests.push(request);
+    Ok(())
+}
+
+// xcall closes the proxy request or rollback account once it is executed and the connection
+// receipt keeps it from being created again, so a record whose requests are all closed can not be
+// replayed. Remaining accounts are the recorded requests in order
+pub fn require_requests_closed(requests: &[Pubkey], remaining_accounts: &[AccountInfo]) -> Result<()> {
+    require_eq!(requests.len(), remaining_accounts.len(), SpokeError::MessageRequestNotClosed);
+    for (request, account) in requests.iter().zip(remaining_accounts) {
+        require_keys_eq!(*request, account.key(), SpokeError::MessageRequestNotClosed);
+        require!(account.lamports() == 0, SpokeError::MessageRequestNotClosed);
+    }
+    Ok(())
+}
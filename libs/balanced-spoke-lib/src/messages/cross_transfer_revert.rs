@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+use rlp::{Encodable, RlpStream};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CrossTransferRevert {
+    pub account: String,
+    pub amount: u128,
+    // network the reverted transfer was headed to, empty for records sent before it was tracked
+    pub to_nid: String,
+    // mint of the burned tokens, only set by spokes that manage more than one mint
+    pub token: String,
+}
+
+pub const CROSS_TRANSFER_REVERT: &str = "xCrossTransferRevert";
+
+// impl Encodable and Decodable for CrossTransferRevert
+impl Encodable for CrossTransferRevert {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        if self.token.is_empty() {
+            s.begin_list(4);
+        } else {
+            s.begin_list(5);
+        }
+        s.append(&CROSS_TRANSFER_REVERT);
+        s.append(&self.account);
+        s.append(&self.amount);
+        s.append(&self.to_nid);
+        if !self.token.is_empty() {
+            s.append(&self.token);
+        }
+    }
+}
+
+impl CrossTransferRevert {
+    pub fn create(account: String, amount: u128, to_nid: String) -> Self {
+        Self {
+            account,
+            amount,
+            to_nid,
+            token: String::new(),
+        }
+    }
+
+    pub fn create_for_token(account: String, amount: u128, to_nid: String, token: String) -> Self {
+        Self {
+            account,
+            amount,
+            to_nid,
+            token,
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        rlp::encode(&self.clone()).to_vec()
+    }
+}
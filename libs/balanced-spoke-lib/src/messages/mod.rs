@@ -0,0 +1,2 @@
+pub mod cross_transfer;
+pub mod cross_transfer_revert;
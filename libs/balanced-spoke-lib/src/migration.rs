@@ -0,0 +1,32 @@
+use anchor_lang::{prelude::*, system_program};
+
+// grows an account created by an earlier program version to `space` bytes, `payer` tops up the
+// rent. Fields are only ever appended, so the old data stays a valid prefix and the added
+// fields read as zero. Returns false when the account already has the current size
+pub fn grow_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+) -> Result<bool> {
+    if account.data_len() >= space {
+        return Ok(false);
+    }
+    let top_up = Rent::get()?
+        .minimum_balance(space)
+        .saturating_sub(account.lamports());
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    account.realloc(space, true)?;
+    Ok(true)
+}
@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+use std::str::FromStr;
+use xcall_lib::network_address::NetworkAddress;
+
+use crate::errors::SpokeError;
+
+const EVM_NETWORKS: [&str; 7] = ["eth", "bsc", "avax", "base", "arbitrum", "optimism", "polygon"];
+
+pub fn account_from_network_address(string_network_address: String) -> Result<String> {
+    let parts = string_network_address.split('/').collect::<Vec<&str>>();
+    require!(parts.len() == 2, SpokeError::InvalidNetworkAddress);
+    Ok(parts[1].to_string())
+}
+
+// the account is checked against the address format of its network, networks without a
+// known format only need a non-empty account
+pub fn is_valid_network_address(address: &str) -> bool {
+    let network_address = match NetworkAddress::from_str(address) {
+        Ok(network_address) => network_address,
+        Err(_) => return false,
+    };
+    let nid = network_address.nid();
+    let account = network_address.account();
+    if nid.is_empty() || account.is_empty() || account.contains('/') {
+        return false;
+    }
+    if nid.ends_with(".icon") {
+        is_hex_address(&account, "hx") || is_hex_address(&account, "cx")
+    } else if nid.starts_with("solana") {
+        Pubkey::from_str(&account).is_ok()
+    } else if is_evm_network(&nid) {
+        is_hex_address(&account, "0x")
+    } else {
+        true
+    }
+}
+
+// nids are `<chain id>.<name>`, a hex chain id alone does not make a network EVM
+fn is_evm_network(nid: &str) -> bool {
+    nid.split_once('.').map_or(false, |(chain_id, name)| {
+        chain_id.starts_with("0x") && EVM_NETWORKS.contains(&name)
+    })
+}
+
+fn is_hex_address(address: &str, prefix: &str) -> bool {
+    address.strip_prefix(prefix).map_or(false, |hex| {
+        hex.len() == 40 && hex.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
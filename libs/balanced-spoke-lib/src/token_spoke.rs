@@ -0,0 +1,366 @@
+// accounts and handlers shared by the spokes that mint their token on inbound transfers, the
+// balanced dollar and spoke token programs. Both macros are expanded inside the program so the
+// accounts are owned by it, `$error` is the program error enum
+
+pub const EXECUTION_ALLOWLIST_SEED: &'static [u8; 19] = b"execution_allowlist";
+pub const MAX_ALLOWED_PROGRAMS: usize = 10;
+pub const MINT_AUTHORITY_TRANSFER_SEED: &'static [u8; 23] = b"mint_authority_transfer";
+// delay between proposing a mint authority handover and the new authority accepting it
+pub const MINT_AUTHORITY_TIMELOCK: i64 = 2 * 24 * 60 * 60;
+
+// expanded in `states.rs`, next to the program `State`
+#[macro_export]
+macro_rules! token_spoke_accounts {
+    ($error:ident) => {
+        #[derive(::anchor_lang::Accounts)]
+        pub struct ManageFeePool<'info> {
+            #[account(seeds=[STATE_SEED], bump)]
+            pub state: Account<'info, State>,
+            /// CHECK: fee pool pda, only holds the lamports used to reimburse relayers
+            #[account(mut, seeds=[FEE_POOL_SEED], bump)]
+            pub fee_pool: AccountInfo<'info>,
+            #[account(mut, address=state.admin @$error::OnlyAdmin)]
+            pub admin: Signer<'info>,
+            pub system_program: Program<'info, System>,
+        }
+
+        #[derive(::anchor_lang::Accounts)]
+        pub struct ConfigureExecutionAllowlist<'info> {
+            #[account(init_if_needed, payer = admin, seeds=[EXECUTION_ALLOWLIST_SEED], bump, space = 8 + ExecutionAllowlist::INIT_SPACE)]
+            pub execution_allowlist: Account<'info, ExecutionAllowlist>,
+            #[account(seeds=[STATE_SEED], bump)]
+            pub state: Account<'info, State>,
+            #[account(mut, address=state.admin @$error::OnlyAdmin)]
+            pub admin: Signer<'info>,
+            pub system_program: Program<'info, System>,
+        }
+
+        #[derive(::anchor_lang::Accounts)]
+        #[instruction(from: String, data: Vec<u8>)]
+        pub struct SkipCallData<'info> {
+            #[account(seeds=[STATE_SEED], bump)]
+            pub state: Account<'info, State>,
+            #[account(
+                init_if_needed,
+                payer = authority,
+                space = 8 + MessageRecord::INIT_SPACE,
+                seeds = [MESSAGE_RECORD_SEED, &message_hash(&from, &data)],
+                bump
+            )]
+            pub message_record: Account<'info, MessageRecord>,
+            // recipient of the transfer or the admin
+            #[account(mut)]
+            pub authority: Signer<'info>,
+            pub system_program: Program<'info, System>,
+        }
+
+        // programs that `xCrossTransfer` call data is allowed to invoke
+        #[::anchor_lang::account]
+        #[derive(::anchor_lang::InitSpace)]
+        pub struct ExecutionAllowlist {
+            #[max_len(10)]
+            pub programs: Vec<::anchor_lang::prelude::Pubkey>,
+        }
+
+        // mint authority handover proposed by the admin, `new_authority` can accept it once `eta`
+        // has passed
+        #[::anchor_lang::account]
+        #[derive(::anchor_lang::InitSpace)]
+        pub struct MintAuthorityTransfer {
+            pub mint: ::anchor_lang::prelude::Pubkey,
+            pub new_authority: ::anchor_lang::prelude::Pubkey,
+            pub eta: i64,
+        }
+
+        impl MintAuthorityTransfer {
+            // `mint_authority` is the program pda holding the authority, handing it to itself
+            // would be a no-op
+            pub fn propose(
+                &mut self,
+                mint: ::anchor_lang::prelude::Pubkey,
+                new_authority: ::anchor_lang::prelude::Pubkey,
+                mint_authority: ::anchor_lang::prelude::Pubkey,
+            ) -> ::anchor_lang::Result<()> {
+                require!(
+                    new_authority != ::anchor_lang::prelude::Pubkey::default()
+                        && new_authority != mint_authority,
+                    $error::InvalidMintAuthority
+                );
+                let eta = Clock::get()?.unix_timestamp + $crate::token_spoke::MINT_AUTHORITY_TIMELOCK;
+                self.mint = mint;
+                self.new_authority = new_authority;
+                self.eta = eta;
+                emit!(MintAuthorityTransferProposed {
+                    mint,
+                    new_authority,
+                    eta,
+                });
+                Ok(())
+            }
+
+            pub fn cancel(&self) {
+                emit!(MintAuthorityTransferCancelled {
+                    mint: self.mint,
+                    new_authority: self.new_authority,
+                });
+            }
+        }
+
+        #[::anchor_lang::event]
+        pub struct MintAuthorityTransferProposed {
+            pub mint: ::anchor_lang::prelude::Pubkey,
+            pub new_authority: ::anchor_lang::prelude::Pubkey,
+            pub eta: i64,
+        }
+
+        #[::anchor_lang::event]
+        pub struct MintAuthorityTransferCancelled {
+            pub mint: ::anchor_lang::prelude::Pubkey,
+            pub new_authority: ::anchor_lang::prelude::Pubkey,
+        }
+
+        #[::anchor_lang::event]
+        pub struct MintAuthorityTransferred {
+            pub mint: ::anchor_lang::prelude::Pubkey,
+            pub new_authority: ::anchor_lang::prelude::Pubkey,
+        }
+    };
+}
+
+// expanded in `instructions.rs`, the program `HandleCallMessage`, `CreateSpokeMint` and
+// `AcceptMintAuthority` accounts have the same fields in both programs
+#[macro_export]
+macro_rules! token_spoke_handlers {
+    ($error:ident) => {
+        pub fn fund_fee_pool(ctx: Context<ManageFeePool>, amount: u64) -> Result<()> {
+            ::anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    ::anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.admin.to_account_info(),
+                        to: ctx.accounts.fee_pool.to_account_info(),
+                    },
+                ),
+                amount,
+            )
+        }
+
+        pub fn drain_fee_pool(ctx: Context<ManageFeePool>, amount: u64) -> Result<()> {
+            require!(
+                amount <= $crate::creation_fee::fee_pool_balance(&ctx.accounts.fee_pool)?,
+                $error::InsufficientBalance
+            );
+            $crate::creation_fee::transfer_from_fee_pool(
+                ctx.accounts.fee_pool.to_account_info(),
+                ctx.accounts.admin.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                amount,
+                ctx.bumps.fee_pool,
+            )
+        }
+
+        pub fn add_allowed_program(ctx: Context<ConfigureExecutionAllowlist>, program: Pubkey) -> Result<()> {
+            let allowlist = &mut ctx.accounts.execution_allowlist;
+            if !allowlist.programs.contains(&program) {
+                require!(
+                    allowlist.programs.len() < $crate::token_spoke::MAX_ALLOWED_PROGRAMS,
+                    $error::ExecutionAllowlistFull
+                );
+                allowlist.programs.push(program);
+            }
+            Ok(())
+        }
+
+        pub fn remove_allowed_program(ctx: Context<ConfigureExecutionAllowlist>, program: Pubkey) -> Result<()> {
+            ctx.accounts.execution_allowlist.programs.retain(|p| *p != program);
+            Ok(())
+        }
+
+        // a failing call fails the whole execution, so a transfer whose call can never succeed would
+        // never be minted. Its recipient or the admin can have it minted like a plain transfer
+        pub fn skip_call_data(ctx: Context<SkipCallData>, _from: String, data: Vec<u8>) -> Result<()> {
+            require!(
+                $crate::codec::decode_method(&data)? == $crate::messages::cross_transfer::CROSS_TRANSFER,
+                $error::UnknownMessageType
+            );
+            let message = $crate::codec::decode_cross_transfer(&data)?;
+            let recipient = $crate::network::account_from_network_address(message.to)?;
+            let authority = ctx.accounts.authority.key();
+            require!(
+                recipient == authority.to_string() || authority == ctx.accounts.state.admin,
+                $crate::errors::SpokeError::NotMessageRecipient
+            );
+            ctx.accounts.message_record.call_skipped = true;
+            Ok(())
+        }
+
+        // errors answered with `success: false`, see `call_response`
+        fn is_permanent_failure(code: u32) -> bool {
+            [
+                u32::from($error::InvalidProtocols),
+                u32::from($error::InvalidSender),
+                u32::from($error::UnknownMessageType),
+                u32::from($error::DecoderError),
+                u32::from($error::NotAnAddress),
+                u32::from($error::InvalidNetworkAddress),
+                u32::from($error::InvalidAmount),
+                u32::from($crate::errors::SpokeError::DecoderError),
+                u32::from($crate::errors::SpokeError::InvalidNetworkAddress),
+            ]
+            .contains(&code)
+        }
+
+        impl<'info> HandleCallMessage<'info> {
+            fn record_message_request(&mut self) -> Result<()> {
+                let record = self.message_record.to_account_info();
+                $crate::message_record::record_request(
+                    &mut self.message_record.requests,
+                    &record,
+                    &self.signer.to_account_info(),
+                    &self.system_program.to_account_info(),
+                    &self.instructions_sysvar,
+                    &self.state.xcall,
+                )
+            }
+
+            // mints `amount` to the recipient, less the creation fee minted to the admin when the
+            // relayer had to create the recipient token account
+            fn mint_to_recipient(
+                &self,
+                amount: u64,
+                is_revert: bool,
+                fee_pool_bump: Option<u8>,
+                signer: &[&[&[u8]]],
+            ) -> Result<()> {
+                let recipient_created = $crate::creation_fee::create_recipient_token_account(
+                    $crate::creation_fee::RecipientTokenAccount {
+                        payer: self.signer.to_account_info(),
+                        token_account: self.to.to_account_info(),
+                        authority: self.to_authority.to_account_info(),
+                        mint: self.mint.to_account_info(),
+                        system_program: self.system_program.to_account_info(),
+                        token_program: self.token_program.to_account_info(),
+                        associated_token_program: self.associated_token_program.to_account_info(),
+                    },
+                )?;
+                let fee = $crate::creation_fee::token_account_creation_fee(
+                    $crate::creation_fee::CreationFeeAccounts {
+                        signer: self.signer.to_account_info(),
+                        system_program: self.system_program.to_account_info(),
+                        fee_pool: self.fee_pool.clone(),
+                        fee_pool_bump,
+                        fee_token_account_owner: Some(self.admin_token_account.owner),
+                        configured_fee: Some(self.token_account_creation_pda.fee()?),
+                    },
+                    self.state.relayer_reimbursement,
+                    self.state.admin,
+                    recipient_created,
+                    amount,
+                    is_revert,
+                    self.state.charge_creation_fee_on_revert,
+                )?;
+                if fee > 0 {
+                    self.mint_to(self.admin_token_account.to_account_info(), fee, signer)?;
+                }
+                self.mint_to(self.to.to_account_info(), amount - fee, signer)
+            }
+
+            fn mint_to(&self, to: AccountInfo<'info>, amount: u64, signer: &[&[&[u8]]]) -> Result<()> {
+                ::anchor_spl::token::mint_to(
+                    CpiContext::new_with_signer(
+                        self.token_program.to_account_info(),
+                        ::anchor_spl::token::MintTo {
+                            mint: self.mint.to_account_info(),
+                            to,
+                            authority: self.mint_authority.to_account_info(),
+                        },
+                        signer,
+                    ),
+                    amount,
+                )
+            }
+        }
+
+        impl<'info> CreateSpokeMint<'info> {
+            // the mint is created with the pda as freeze authority, it is dropped unless asked to
+            // keep it
+            fn create_metadata(
+                &self,
+                signer: &[&[&[u8]]],
+                freeze_authority: bool,
+                name: String,
+                symbol: String,
+                uri: String,
+            ) -> Result<()> {
+                ::anchor_spl::metadata::create_metadata_accounts_v3(
+                    CpiContext::new_with_signer(
+                        self.token_metadata_program.to_account_info(),
+                        ::anchor_spl::metadata::CreateMetadataAccountsV3 {
+                            metadata: self.metadata.to_account_info(),
+                            mint: self.mint.to_account_info(),
+                            mint_authority: self.mint_authority.to_account_info(),
+                            payer: self.admin.to_account_info(),
+                            update_authority: self.mint_authority.to_account_info(),
+                            system_program: self.system_program.to_account_info(),
+                            rent: self.rent.to_account_info(),
+                        },
+                        signer,
+                    ),
+                    ::anchor_spl::metadata::mpl_token_metadata::types::DataV2 {
+                        name,
+                        symbol,
+                        uri,
+                        seller_fee_basis_points: 0,
+                        creators: None,
+                        collection: None,
+                        uses: None,
+                    },
+                    true,
+                    true,
+                    None,
+                )?;
+                if !freeze_authority {
+                    ::anchor_spl::token::set_authority(
+                        CpiContext::new_with_signer(
+                            self.token_program.to_account_info(),
+                            ::anchor_spl::token::SetAuthority {
+                                current_authority: self.mint_authority.to_account_info(),
+                                account_or_mint: self.mint.to_account_info(),
+                            },
+                            signer,
+                        ),
+                        ::anchor_spl::token::spl_token::instruction::AuthorityType::FreezeAccount,
+                        None,
+                    )?;
+                }
+                Ok(())
+            }
+        }
+
+        impl<'info> AcceptMintAuthority<'info> {
+            fn hand_over(&self, signer: &[&[&[u8]]]) -> Result<()> {
+                require!(
+                    Clock::get()?.unix_timestamp >= self.mint_authority_transfer.eta,
+                    $error::MintAuthorityTimelockActive
+                );
+                ::anchor_spl::token::set_authority(
+                    CpiContext::new_with_signer(
+                        self.token_program.to_account_info(),
+                        ::anchor_spl::token::SetAuthority {
+                            current_authority: self.mint_authority.to_account_info(),
+                            account_or_mint: self.mint.to_account_info(),
+                        },
+                        signer,
+                    ),
+                    ::anchor_spl::token::spl_token::instruction::AuthorityType::MintTokens,
+                    Some(self.new_authority.key()),
+                )?;
+                emit!(MintAuthorityTransferred {
+                    mint: self.mint.key(),
+                    new_authority: self.new_authority.key(),
+                });
+                Ok(())
+            }
+        }
+    };
+}
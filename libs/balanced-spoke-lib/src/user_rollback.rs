@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SpokeError;
+
+pub const ROLLBACK_REQUEST_SEED: &'static [u8; 16] = b"rollback_request";
+// delay between a failed inbound message being recorded and its recipient being able to force
+// its rollback, the operator can still execute the message until then
+pub const DEFAULT_USER_ROLLBACK_TIMEOUT: i64 = 24 * 60 * 60;
+pub const MIN_USER_ROLLBACK_TIMEOUT: i64 = 60 * 60;
+
+const PROXY_REQUEST_DISCRIMINATOR: [u8; 8] = [218, 221, 67, 191, 4, 58, 233, 238];
+
+// layout of the xcall `ProxyRequest` account, which holds an inbound request until it is executed
+#[derive(AnchorDeserialize)]
+pub struct ProxyRequest {
+    pub from: String,
+    pub to: String,
+    pub sequence_no: u128,
+    pub msg_type: u8,
+    pub data: Vec<u8>,
+    pub protocols: Vec<String>,
+    pub bump: u8,
+}
+
+pub fn read_proxy_request(proxy_request: &AccountInfo, xcall: &Pubkey) -> Result<ProxyRequest> {
+    require_keys_eq!(*proxy_request.owner, *xcall, SpokeError::InvalidProxyRequest);
+    let data = proxy_request.try_borrow_data()?;
+    require!(
+        data.len() > 8 && data[..8] == PROXY_REQUEST_DISCRIMINATOR,
+        SpokeError::InvalidProxyRequest
+    );
+    ProxyRequest::deserialize(&mut &data[8..]).map_err(|_| SpokeError::InvalidProxyRequest.into())
+}
@@ -0,0 +1,227 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        instruction::Instruction,
+        program::{get_return_data, invoke_signed},
+    },
+    InstructionData,
+};
+use std::str::FromStr;
+use xcall::cpi::accounts::{HandleForcedRollbackCtx, SendCallCtx};
+use xcall_lib::message::{
+    call_message_rollback::CallMessageWithRollback, envelope::Envelope, AnyMessage,
+};
+use xcall_lib::network_address::NetworkAddress;
+use xcall_manager::{cpi::accounts::VerifyProtocols, XmState};
+
+use crate::{authority::DAPP_AUTHORITY_SEED, errors::SpokeError, xcall_pda::read_config};
+
+// accounts shared by every xcall cpi a spoke makes, `dapp_authority` signs with its bump
+pub struct XcallCpiAccounts<'a, 'info> {
+    pub xcall: AccountInfo<'info>,
+    pub signer: AccountInfo<'info>,
+    pub dapp_authority: AccountInfo<'info>,
+    pub dapp_authority_bump: u8,
+    pub system_program: AccountInfo<'info>,
+    pub remaining_accounts: &'a [AccountInfo<'info>],
+}
+
+// sends `data` to the hub contract at `to` with `rollback` returned on failure, remaining
+// accounts are [config, rollback, sysvar, fee_handler, ...connection accounts]
+pub fn send_message_with_rollback<'info>(
+    accounts: XcallCpiAccounts<'_, 'info>,
+    to: &str,
+    data: Vec<u8>,
+    rollback: Vec<u8>,
+    xm_state: &XmState,
+) -> Result<u128> {
+    let message = AnyMessage::CallMessageWithRollback(CallMessageWithRollback { data, rollback });
+    let envelope: Envelope =
+        Envelope::new(message, xm_state.sources.clone(), xm_state.destinations.clone());
+    let envelope_encoded = rlp::encode(&envelope).to_vec();
+
+    let to = NetworkAddress::from_str(to).map_err(|_| SpokeError::InvalidNetworkAddress)?;
+    require!(accounts.remaining_accounts.len() >= 4, SpokeError::XcallAccountsRequired);
+    let xcall_config = &accounts.remaining_accounts[0];
+    let rollback_account = &accounts.remaining_accounts[1];
+    let sysvar_account = &accounts.remaining_accounts[2];
+    let fee_handler = &accounts.remaining_accounts[3];
+    // the accounts for centralized connections is contained here.
+    let remaining_accounts = accounts.remaining_accounts.split_at(4).1;
+    let cpi_accounts: SendCallCtx = SendCallCtx {
+        config: xcall_config.to_account_info(),
+        rollback_account: Some(rollback_account.to_account_info()),
+        fee_handler: fee_handler.to_account_info(),
+        signer: accounts.signer,
+        instruction_sysvar: sysvar_account.to_account_info(),
+        dapp_authority: Some(accounts.dapp_authority),
+        system_program: accounts.system_program,
+    };
+    let data = xcall::instruction::SendCall { envelope: envelope_encoded, to }.data();
+    invoke_xcall(
+        &accounts.xcall,
+        cpi_accounts,
+        remaining_accounts,
+        data,
+        accounts.dapp_authority_bump,
+    )?;
+    match get_return_data() {
+        Some((program_id, sn)) if program_id == accounts.xcall.key() => {
+            Ok(u128::try_from_slice(&sn)?)
+        }
+        _ => Err(SpokeError::InvalidXcallProgram.into()),
+    }
+}
+
+// remaining accounts are [proxy_request, config, admin, ...connection accounts]
+// xcall accounts of a forced rollback, validated by the calling program
+pub struct ForcedRollbackAccounts<'info> {
+    pub proxy_request: AccountInfo<'info>,
+    pub config: AccountInfo<'info>,
+    pub admin: AccountInfo<'info>,
+}
+
+// the remaining accounts are the connection accounts used to send the rollback
+pub fn force_rollback<'info>(
+    accounts: XcallCpiAccounts<'_, 'info>,
+    rollback_accounts: ForcedRollbackAccounts<'info>,
+    request_id: u128,
+    source_nid: String,
+    connection_sn: u128,
+    dst_program_id: Pubkey,
+) -> Result<()> {
+    let config = read_config(&rollback_accounts.config, &accounts.xcall.key())?;
+    require_keys_eq!(rollback_accounts.admin.key(), config.admin, SpokeError::InvalidXcallAdmin);
+    let cpi_accounts: HandleForcedRollbackCtx = HandleForcedRollbackCtx {
+        proxy_request: rollback_accounts.proxy_request,
+        signer: accounts.signer,
+        dapp_authority: accounts.dapp_authority,
+        config: rollback_accounts.config,
+        system_program: accounts.system_program,
+        admin: rollback_accounts.admin,
+    };
+    let data = xcall::instruction::HandleForcedRollback {
+        req_id: request_id,
+        from_nid: source_nid,
+        conn_sn: connection_sn,
+        connection: dst_program_id,
+    }
+    .data();
+    invoke_xcall(
+        &accounts.xcall,
+        cpi_accounts,
+        accounts.remaining_accounts,
+        data,
+        accounts.dapp_authority_bump,
+    )
+}
+
+// declares the `ForceRollback` accounts of the admin forced rollback, `$error::$only_admin` is
+// raised when the signer is not the state admin. It has to be expanded inside the program next to
+// its `State` and `Authority` accounts
+#[macro_export]
+macro_rules! force_rollback {
+    ($error:ident :: $only_admin:ident) => {
+        #[derive(::anchor_lang::Accounts)]
+        #[instruction(request_id: u128, source_nid: String, connection_sn: u128, dst_program_id: Pubkey)]
+        pub struct ForceRollback<'info> {
+            #[account(seeds = [STATE_SEED], bump)]
+            pub state: Account<'info, State>,
+            #[account(mut, address=state.admin @$error::$only_admin)]
+            pub signer: Signer<'info>,
+            /// CHECK: xcall proxy request of the message, checked against the xcall pda
+            #[account(
+                mut,
+                address = proxy_request_address(&state.xcall, &source_nid, connection_sn, &dst_program_id)
+                    @SpokeError::InvalidProxyRequest
+            )]
+            pub proxy_request: AccountInfo<'info>,
+            /// CHECK: config of the xcall set in the state
+            #[account(
+                mut,
+                address = config_address(&state.xcall) @SpokeError::InvalidXcallConfig,
+                owner = state.xcall @SpokeError::InvalidXcallConfig
+            )]
+            pub xcall_config: UncheckedAccount<'info>,
+            /// CHECK: xcall admin, checked against the xcall config before the rollback
+            #[account(mut)]
+            pub xcall_admin: AccountInfo<'info>,
+            /// CHECK: the xcall set in the state, which may differ from the one the program was built against
+            #[account(
+                address = state.xcall @SpokeError::InvalidXcallProgram,
+                constraint = xcall.executable @SpokeError::InvalidXcallProgram
+            )]
+            pub xcall: UncheckedAccount<'info>,
+            #[account(
+              init_if_needed, payer=signer, space = Authority::MAX_SPACE, seeds = [Authority::SEED_PREFIX], bump
+            )]
+            pub xcall_authority: Account<'info, Authority>,
+            pub system_program: Program<'info, System>,
+        }
+
+        impl<'info> ForceRollback<'info> {
+            pub fn force_rollback(
+                &self,
+                xcall_authority_bump: u8,
+                remaining_accounts: &[AccountInfo<'info>],
+                request_id: u128,
+                source_nid: String,
+                connection_sn: u128,
+                dst_program_id: Pubkey,
+            ) -> ::anchor_lang::Result<()> {
+                $crate::xcall_cpi::force_rollback(
+                    $crate::xcall_cpi::XcallCpiAccounts {
+                        xcall: self.xcall.to_account_info(),
+                        signer: self.signer.to_account_info(),
+                        dapp_authority: self.xcall_authority.to_account_info(),
+                        dapp_authority_bump: xcall_authority_bump,
+                        system_program: self.system_program.to_account_info(),
+                        remaining_accounts,
+                    },
+                    $crate::xcall_cpi::ForcedRollbackAccounts {
+                        proxy_request: self.proxy_request.to_account_info(),
+                        config: self.xcall_config.to_account_info(),
+                        admin: self.xcall_admin.to_account_info(),
+                    },
+                    request_id,
+                    source_nid,
+                    connection_sn,
+                    dst_program_id,
+                )
+            }
+        }
+    };
+}
+
+// the generated xcall cpi always targets the xcall the spoke was built against, the instruction is
+// built here so it goes to the xcall configured in the spoke state
+fn invoke_xcall<'info>(
+    xcall: &AccountInfo<'info>,
+    accounts: impl ToAccountMetas + ToAccountInfos<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    data: Vec<u8>,
+    dapp_authority_bump: u8,
+) -> Result<()> {
+    require!(xcall.executable, SpokeError::InvalidXcallProgram);
+    let mut metas = accounts.to_account_metas(None);
+    metas.extend(remaining_accounts.iter().flat_map(|account| account.to_account_metas(None)));
+    let mut infos = accounts.to_account_infos();
+    infos.extend_from_slice(remaining_accounts);
+    infos.push(xcall.clone());
+    let ix = Instruction { program_id: xcall.key(), accounts: metas, data };
+    let seeds = &[DAPP_AUTHORITY_SEED.as_ref(), &[dapp_authority_bump]];
+    invoke_signed(&ix, &infos, &[&seeds[..]])?;
+    Ok(())
+}
+
+pub fn verify_protocols<'info>(
+    xcall_manager_program: AccountInfo<'info>,
+    xm_state: AccountInfo<'info>,
+    protocols: &[String],
+) -> Result<bool> {
+    let cpi_accounts: VerifyProtocols = VerifyProtocols { state: xm_state };
+
+    let cpi_ctx = CpiContext::new(xcall_manager_program, cpi_accounts);
+    let verified = xcall_manager::cpi::verify_protocols(cpi_ctx, protocols.to_vec())?;
+    Ok(verified.get())
+}
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+use xcall::state::Config;
+
+use crate::errors::SpokeError;
+
+// pdas of the xcall program that spokes pass on to it and check beforehand
+
+pub const XCALL_CONFIG_SEED: &'static [u8; 6] = b"config";
+pub const PROXY_REQUEST_SEED: &'static [u8; 5] = b"proxy";
+
+pub fn config_address(xcall: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[XCALL_CONFIG_SEED], xcall).0
+}
+
+pub fn proxy_request_address(
+    xcall: &Pubkey,
+    source_nid: &str,
+    connection_sn: u128,
+    connection: &Pubkey,
+) -> Pubkey {
+    let seeds: &[&[u8]] = &[
+        PROXY_REQUEST_SEED,
+        source_nid.as_bytes(),
+        &connection_sn.to_be_bytes(),
+        connection.as_ref(),
+    ];
+    Pubkey::find_program_address(seeds, xcall).0
+}
+
+// reads the config of the xcall set in the spoke state, which can differ from the xcall the spoke
+// was built against
+pub fn read_config(config: &AccountInfo, xcall: &Pubkey) -> Result<Config> {
+    require_keys_eq!(config.key(), config_address(xcall), SpokeError::InvalidXcallConfig);
+    require_keys_eq!(*config.owner, *xcall, SpokeError::InvalidXcallConfig);
+    Config::try_deserialize(&mut &config.data.borrow()[..])
+}
@@ -65,8 +65,48 @@ pub enum AssetManagerError {
     InvalidValutNativeAuthority,
     #[msg["Invalid Admin"]]
     InvalidAdmin,
-    #[msg("Mint Amount Less than Token Creation Fee")]
-    MintAmountLessThanTokenCreationFee,
+    #[msg("Wrapped native mode is disabled")]
+    WrappedNativeModeDisabled,
+    #[msg("Not the wrapped native mint")]
+    NotWrappedNative,
+    #[msg("Wsol unwrap account is required")]
+    WsolUnwrapAccountIsRequired,
+    #[msg("Wsol must be deposited through deposit_wrapped_native")]
+    WrappedNativeDepositRequired,
+    #[msg("Token is disabled")]
+    TokenDisabled,
+    #[msg("Token registry is full")]
+    TokenRegistryFull,
+    #[msg("Mint decimals do not match the registered decimals")]
+    InvalidDecimals,
+    #[msg("Mint has a freeze authority")]
+    FreezeAuthorityNotAllowed,
+    #[msg("Hub contracts are not configured")]
+    HubContractsNotConfigured,
+    #[msg("Invalid swap path")]
+    InvalidSwapPath,
+    #[msg("Invalid network address")]
+    InvalidNetworkAddress,
+    #[msg("Only the program upgrade authority can initialize")]
+    OnlyUpgradeAuthority,
+    #[msg("Only the recipient of the message can request a rollback")]
+    NotMessageRecipient,
+    #[msg("Rollback timeout has not passed")]
+    RollbackTimeoutActive,
+    #[msg("Invalid rollback timeout")]
+    InvalidRollbackTimeout,
+    #[msg("Deposit record is required")]
+    DepositRecordRequired,
+    #[msg("Invalid deposit record")]
+    InvalidDepositRecord,
+    #[msg("Deposit is still pending")]
+    DepositPending,
+    #[msg("Invalid token account creation fee account")]
+    InvalidTokenAccountCreationFee,
+    #[msg("Associated token program is required")]
+    AssociatedTokenProgramIsRequired,
+    #[msg("Invalid migration account")]
+    InvalidMigrationAccount,
 }
 
 impl From<DecoderError> for AssetManagerError {
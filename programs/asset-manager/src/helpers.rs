@@ -8,18 +8,6 @@ use crate::{
     },
 };
 
-pub fn decode_method(data: &[u8]) -> Result<String, AssetManagerError> {
-    let rlp: Rlp = Rlp::new(data);
-
-    if !rlp.is_list() {
-        return Err(DecoderError::RlpExpectedToBeList.into());
-    }
-
-    let method: String = rlp.val_at(0)?;
-
-    Ok(method)
-}
-
 pub fn decode_token_address(data: &[u8]) -> std::result::Result<String, AssetManagerError> {
     let rlp: Rlp = Rlp::new(data);
 
@@ -62,7 +50,8 @@ pub fn decode_deposit_revert_msg(
     if !rlp.is_list() {
         return Err(DecoderError::RlpExpectedToBeList.into());
     }
-    if rlp.item_count()? != 4 {
+    let item_count = rlp.item_count()?;
+    if item_count != 4 && item_count != 5 {
         return Err(DecoderError::RlpInvalidLength.into());
     }
 
@@ -73,11 +62,13 @@ pub fn decode_deposit_revert_msg(
     let token_address = rlp.val_at(1)?;
     let account = rlp.val_at(2)?;
     let amount: u64 = rlp.val_at(3)?;
+    let sn = if item_count == 5 { Some(rlp.val_at(4)?) } else { None };
 
     let deposit_revert: DepositRevert = DepositRevert {
         token_address,
         account,
         amount,
+        sn,
     };
     Ok(deposit_revert)
 }
@@ -1,24 +1,33 @@
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::program_pack::Pack;
-use anchor_spl::token::{self, spl_token, TokenAccount, Transfer};
+use anchor_lang::solana_program::{program_pack::Pack, system_program, sysvar};
+use anchor_spl::token::{
+    self, spl_token, CloseAccount, InitializeAccount3, Mint, SyncNative, TokenAccount, Transfer,
+};
 
-use std::str::FromStr;
-use xcall::cpi::accounts::{SendCallCtx, HandleForcedRollbackCtx};
-use xcall_lib::message::{
-    call_message_rollback::CallMessageWithRollback, envelope::Envelope, AnyMessage,
+use balanced_spoke_lib::{
+    call_response::call_response,
+    codec::decode_method,
+    creation_fee::{
+        self, fee_pool_balance, transfer_from_fee_pool, CreationFeeAccounts, RecipientTokenAccount,
+    },
+    errors::SpokeError,
+    message_record::{self, message_record_address},
+    migration::grow_account,
+    network::is_valid_network_address,
+    user_rollback::{read_proxy_request, DEFAULT_USER_ROLLBACK_TIMEOUT, MIN_USER_ROLLBACK_TIMEOUT},
+    xcall_cpi::{self, verify_protocols, ForcedRollbackAccounts, XcallCpiAccounts},
+    xcall_pda::read_config,
 };
+use std::str::FromStr;
 use xcall_lib::network_address::NetworkAddress;
 use xcall_lib::xcall_dapp_type::HandleCallMessageResponse;
-use xcall_manager::{cpi::accounts::VerifyProtocols, program::XcallManager, XmState};
 
 use crate::errors::AssetManagerError;
-use crate::helpers::{
-    decode_deposit_revert_msg, decode_method, decode_token_address, decode_withdraw_to_msg,
-};
+use crate::helpers::{decode_deposit_revert_msg, decode_token_address, decode_withdraw_to_msg};
 use crate::{
     param_accounts::*,
     states::*,
-    structs::{deposit_message::*, deposit_revert::*, withdraw_message::*},
+    structs::{deposit_message::*, deposit_revert::*, hub_action::*, withdraw_message::*},
 };
 
 const POINTS: u64 = 10000;
@@ -31,12 +40,26 @@ pub fn initialize(
     xcall_manager: Pubkey,
     xcall_manager_state: Pubkey,
 ) -> Result<()> {
+    verify_network_address(&icon_asset_manager)?;
     let state: &mut Account<State> = &mut ctx.accounts.state;
     state.xcall = xcall;
     state.icon_asset_manager = icon_asset_manager;
     state.xcall_manager = xcall_manager;
     state.xcall_manager_state = xcall_manager_state;
     state.admin = ctx.accounts.admin.key();
+    state.rollback_timeout = DEFAULT_USER_ROLLBACK_TIMEOUT;
+
+    let vault_native_account = &ctx.accounts.vault_native_account;
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(0);
+    let top_up = rent_exempt_minimum.saturating_sub(vault_native_account.lamports());
+    if top_up > 0 {
+        transfer_lamports(
+            ctx.accounts.admin.to_account_info(),
+            vault_native_account.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            top_up,
+        )?;
+    }
     Ok(())
 }
 
@@ -48,14 +71,200 @@ pub fn set_admin(
     return  Ok(());
 }
 
+pub fn update_config(
+    ctx: Context<UpdateConfig>,
+    xcall: Option<Pubkey>,
+    icon_asset_manager: Option<String>,
+    xcall_manager: Option<Pubkey>,
+    xcall_manager_state: Option<Pubkey>,
+) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    if let Some(xcall) = xcall {
+        state.xcall = xcall;
+    }
+    if let Some(icon_asset_manager) = icon_asset_manager {
+        verify_network_address(&icon_asset_manager)?;
+        state.icon_asset_manager = icon_asset_manager;
+    }
+    if let Some(xcall_manager) = xcall_manager {
+        state.xcall_manager = xcall_manager;
+    }
+    if let Some(xcall_manager_state) = xcall_manager_state {
+        state.xcall_manager_state = xcall_manager_state;
+    }
+    emit!(ConfigUpdated {
+        xcall: state.xcall,
+        icon_asset_manager: state.icon_asset_manager.clone(),
+        xcall_manager: state.xcall_manager,
+        xcall_manager_state: state.xcall_manager_state,
+    });
+    Ok(())
+}
+
+pub fn set_wrapped_native_mode(
+    ctx: Context<SetWrappedNativeMode>,
+    enabled: bool) -> Result<()>{
+    let state: &mut Account<State> = &mut ctx.accounts.state;
+    state.wrapped_native_mode = enabled;
+    return  Ok(());
+}
+
+pub fn register_token(
+    ctx: Context<RegisterToken>,
+    token: Pubkey,
+    hub_asset: String,
+    decimals: u8,
+) -> Result<()> {
+    verify_network_address(&hub_asset)?;
+    let token_registry = &mut ctx.accounts.token_registry;
+    require!(token_registry.tokens.len() < MAX_REGISTERED_TOKENS, AssetManagerError::TokenRegistryFull);
+    token_registry.tokens.push(token);
+
+    ctx.accounts.token_config.set_inner(TokenConfig {
+        token,
+        decimals,
+        enabled: true,
+        hub_asset,
+    });
+    Ok(())
+}
+
+pub fn update_token_config(
+    ctx: Context<UpdateTokenConfig>,
+    _token: Pubkey,
+    hub_asset: String,
+    decimals: u8,
+    enabled: bool,
+) -> Result<()> {
+    verify_network_address(&hub_asset)?;
+    let token_config = &mut ctx.accounts.token_config;
+    token_config.hub_asset = hub_asset;
+    token_config.decimals = decimals;
+    token_config.enabled = enabled;
+    Ok(())
+}
+
+pub fn register_token_vault(
+    ctx: Context<RegisterTokenVault>,
+    period: u64,
+    percentage: u64,
+    token_account_creation_fee: u64,
+    allow_freeze_authority: bool,
+) -> Result<()> {
+    require!(percentage <= POINTS, AssetManagerError::PercentageTooHigh);
+    let mint = &ctx.accounts.mint;
+    require!(
+        mint.decimals == ctx.accounts.token_config.decimals,
+        AssetManagerError::InvalidDecimals
+    );
+    // a freeze authority can lock the vault, so it has to be accepted explicitly
+    require!(
+        allow_freeze_authority || mint.freeze_authority.is_none(),
+        AssetManagerError::FreezeAuthorityNotAllowed
+    );
+
+    let token = mint.key();
+    ctx.accounts.token_state.set_inner(TokenState {
+        token,
+        period,
+        percentage,
+        last_update: Clock::get()?.unix_timestamp,
+        current_limit: 0,
+        total_deposited: 0,
+        total_withdrawn: 0,
+        total_reverted: 0,
+    });
+    ctx.accounts.token_account_creation_pda.set_inner(TokenAccountCreationFee {
+        token,
+        token_account_creation_fee,
+        fee_mode: TokenAccountCreationFeeMode::Fixed,
+        tokens_per_sol: 0,
+    });
+    Ok(())
+}
+
+pub fn list_tokens(ctx: Context<ListTokens>) -> Result<Vec<Pubkey>> {
+    Ok(ctx.accounts.token_registry.tokens.clone())
+}
+
+// xcall closes the rollback account once the hub confirms the deposit, reverts update the record
+// themselves. Anyone can call it, a settled record is closed back to the depositor
+pub fn sync_deposit_record(ctx: Context<SyncDepositRecord>) -> Result<DepositStatus> {
+    let record = &mut ctx.accounts.deposit_record;
+    if record.status == DepositStatus::Pending && ctx.accounts.rollback_account.lamports() == 0 {
+        record.status = DepositStatus::Executed;
+    }
+    let status = record.status;
+    if status != DepositStatus::Pending {
+        record.close(ctx.accounts.from.to_account_info())?;
+    }
+    Ok(status)
+}
+
+pub fn set_creation_fee_policy(
+    ctx: Context<SetCreationFeePolicy>,
+    charge_on_revert: bool) -> Result<()>{
+    let state: &mut Account<State> = &mut ctx.accounts.state;
+    state.charge_creation_fee_on_revert = charge_on_revert;
+    return  Ok(());
+}
+
+pub fn set_relayer_reimbursement(
+    ctx: Context<SetRelayerReimbursement>,
+    relayer_reimbursement: RelayerReimbursement) -> Result<()>{
+    let state: &mut Account<State> = &mut ctx.accounts.state;
+    state.relayer_reimbursement = relayer_reimbursement;
+    return  Ok(());
+}
+
+pub fn fund_fee_pool(ctx: Context<ManageFeePool>, amount: u64) -> Result<()> {
+    transfer_lamports(
+        ctx.accounts.admin.to_account_info(),
+        ctx.accounts.fee_pool.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        amount,
+    )
+}
+
+pub fn drain_fee_pool(ctx: Context<ManageFeePool>, amount: u64) -> Result<()> {
+    require!(
+        amount <= fee_pool_balance(&ctx.accounts.fee_pool)?,
+        AssetManagerError::InsufficientBalance
+    );
+    transfer_from_fee_pool(
+        ctx.accounts.fee_pool.to_account_info(),
+        ctx.accounts.admin.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        amount,
+        ctx.bumps.fee_pool,
+    )
+}
+
+pub fn set_hub_contracts(
+    ctx: Context<SetHubContracts>,
+    router: String,
+    loans: String,
+) -> Result<()> {
+    verify_network_address(&router)?;
+    verify_network_address(&loans)?;
+    let hub_contracts = &mut ctx.accounts.hub_contracts;
+    hub_contracts.router = router;
+    hub_contracts.loans = loans;
+    Ok(())
+}
+
 pub fn set_token_account_creation_fee(
     ctx: Context<SetTokenAccountCreationFee>,
     token: Pubkey,
-    token_account_creation_fee: u64
+    token_account_creation_fee: u64,
+    fee_mode: TokenAccountCreationFeeMode,
+    tokens_per_sol: u64,
 ) -> Result<()> {
     let token_account_creation_pda: &mut Account<TokenAccountCreationFee> = &mut ctx.accounts.token_account_creation_pda;
     token_account_creation_pda.token = token;
     token_account_creation_pda.token_account_creation_fee = token_account_creation_fee;
+    token_account_creation_pda.fee_mode = fee_mode;
+    token_account_creation_pda.tokens_per_sol = tokens_per_sol;
     Ok(())
 }
 
@@ -71,13 +280,12 @@ pub fn configure_rate_limit(
     let token_state: &mut Account<TokenState> = &mut ctx.accounts.token_state;
     let current_limit = 0;
     let last_update = Clock::get()?.unix_timestamp;
-    token_state.set_inner(TokenState {
-        token,
-        period,
-        percentage,
-        last_update,
-        current_limit
-    });
+    // the vault counters are kept when the limit is reconfigured
+    token_state.token = token;
+    token_state.period = period;
+    token_state.percentage = percentage;
+    token_state.last_update = last_update;
+    token_state.current_limit = current_limit;
     Ok(())
 }
 
@@ -110,6 +318,35 @@ pub fn get_withdraw_limit(ctx: Context<GetWithdrawLimit>) -> Result<u64> {
     calculate_limit(token_state, balance)
 }
 
+pub fn get_native_withdraw_limit(ctx: Context<GetNativeWithdrawLimit>) -> Result<u64> {
+    let token_state = &ctx.accounts.token_state;
+    let balance = native_vault_balance(&ctx.accounts.vault_native_account)?;
+    calculate_limit(token_state, balance)
+}
+
+pub fn get_vault_accounting(ctx: Context<GetVaultAccounting>) -> Result<VaultAccounting> {
+    let balance = balance_of(&ctx.accounts.vault_token_account)?;
+    Ok(vault_accounting(&ctx.accounts.token_state, balance))
+}
+
+pub fn get_native_vault_accounting(ctx: Context<GetNativeWithdrawLimit>) -> Result<VaultAccounting> {
+    let balance = native_vault_balance(&ctx.accounts.vault_native_account)?;
+    Ok(vault_accounting(&ctx.accounts.token_state, balance))
+}
+
+fn vault_accounting(token_state: &TokenState, vault_balance: u64) -> VaultAccounting {
+    let expected = token_state.total_deposited as i128
+        - token_state.total_withdrawn as i128
+        - token_state.total_reverted as i128;
+    VaultAccounting {
+        deposited: token_state.total_deposited,
+        withdrawn: token_state.total_withdrawn,
+        reverted: token_state.total_reverted,
+        vault_balance,
+        delta: vault_balance as i128 - expected,
+    }
+}
+
 pub fn deposit_token<'info>(
     ctx: Context<'_, '_, '_, 'info, DepositToken<'info>>,
     amount: u64,
@@ -117,6 +354,7 @@ pub fn deposit_token<'info>(
     data: Option<Vec<u8>>,
 ) -> Result<u128> {
     require!(amount > 0, AssetManagerError::InvalidAmount);
+    verify_to_address(&to)?;
     
     let from = ctx
         .accounts
@@ -124,6 +362,11 @@ pub fn deposit_token<'info>(
         .as_ref()
         .ok_or(AssetManagerError::InvalidFromAddress)?;
     let token_addr = from.mint;
+    verify_token_config(&ctx.accounts.token_config, token_addr, true)?;
+    require!(
+        !(ctx.accounts.state.wrapped_native_mode && token_addr == spl_token::native_mint::ID),
+        AssetManagerError::WrappedNativeDepositRequired
+    );
     require!(ctx.accounts.valult_authority.clone().unwrap().key()==get_vault_pda(&ctx.program_id, token_addr)?.0, AssetManagerError::InvalidValutAuthority);
     let vault_token_account = ctx
         .accounts
@@ -158,6 +401,8 @@ pub fn deposit_native<'info>(
     data: Option<Vec<u8>>,
 ) -> Result<u128> {
     require!(amount > 0, AssetManagerError::InvalidAmount);
+    verify_to_address(&to)?;
+    verify_token_config(&ctx.accounts.token_config, system_program::ID, true)?;
     require!(ctx.accounts.vault_native_account.clone().unwrap().key()==get_native_vault_pda(&ctx.program_id)?.0, AssetManagerError::InvalidValutNativeAuthority);
     let vault_native_account = ctx
         .accounts
@@ -166,23 +411,16 @@ pub fn deposit_native<'info>(
         .ok_or(AssetManagerError::ValultTokenAccountIsRequired)?;
     let user = &ctx.accounts.from_authority;
 
-    let transfer_instruction = spl_token::solana_program::system_instruction::transfer(
-        &user.key(),
-        &vault_native_account.key(),
+    transfer_lamports(
+        user.to_account_info(),
+        vault_native_account.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
         amount,
-    );
-    spl_token::solana_program::program::invoke(
-        &transfer_instruction,
-        &[
-            user.to_account_info(),
-            vault_native_account.to_account_info(),
-            ctx.accounts.system_program.to_account_info(),
-        ],
     )?;
     let from: Pubkey = user.key();
     let res = send_deposit_message(
         ctx,
-        String::from_str(_NATIVE_ADDRESS).unwrap(),
+        _NATIVE_ADDRESS.to_string(),
         from,
         amount,
         to,
@@ -191,6 +429,201 @@ pub fn deposit_native<'info>(
     Ok(res)
 }
 
+pub fn deposit_wrapped_native<'info>(
+    ctx: Context<'_, '_, '_, 'info, DepositToken<'info>>,
+    amount: u64,
+    to: Option<String>,
+    data: Option<Vec<u8>>,
+) -> Result<u128> {
+    require!(amount > 0, AssetManagerError::InvalidAmount);
+    verify_to_address(&to)?;
+    require!(ctx.accounts.state.wrapped_native_mode, AssetManagerError::WrappedNativeModeDisabled);
+    verify_token_config(&ctx.accounts.token_config, system_program::ID, true)?;
+    require!(ctx.accounts.vault_native_account.clone().unwrap().key()==get_native_vault_pda(&ctx.program_id)?.0, AssetManagerError::InvalidValutNativeAuthority);
+    let from = ctx
+        .accounts
+        .from
+        .as_ref()
+        .ok_or(AssetManagerError::InvalidFromAddress)?;
+    require!(from.mint == spl_token::native_mint::ID, AssetManagerError::NotWrappedNative);
+    let vault_native_account = ctx
+        .accounts
+        .vault_native_account
+        .as_ref()
+        .ok_or(AssetManagerError::ValultTokenAccountIsRequired)?;
+    let wsol_unwrap_account = ctx
+        .accounts
+        .wsol_unwrap_account
+        .as_ref()
+        .ok_or(AssetManagerError::WsolUnwrapAccountIsRequired)?;
+    let native_mint = ctx
+        .accounts
+        .native_mint
+        .as_ref()
+        .ok_or(AssetManagerError::MintIsRequired)?;
+    let token_program = ctx
+        .accounts
+        .token_program
+        .as_ref()
+        .ok_or(AssetManagerError::TokenProgramIsRequired)?
+        .to_account_info();
+    let user = &ctx.accounts.from_authority;
+    let system_program = ctx.accounts.system_program.to_account_info();
+
+    let user_key = user.key();
+    let bump = ctx.bumps.wsol_unwrap_account.unwrap();
+    let seeds = &[WSOL_UNWRAP_SEED.as_ref(), user_key.as_ref(), &[bump]];
+    let signer = &[&seeds[..]];
+
+    // only `amount` is moved out of the user's wsol account, so it is unwrapped
+    // through a temporary account that is closed back to the user. It is funded, allocated and
+    // assigned separately since `create_account` fails once anyone sent lamports to the address
+    let space = spl_token::state::Account::LEN;
+    let top_up = Rent::get()?
+        .minimum_balance(space)
+        .saturating_sub(wsol_unwrap_account.lamports());
+    if top_up > 0 {
+        transfer_lamports(
+            user.to_account_info(),
+            wsol_unwrap_account.clone(),
+            system_program.clone(),
+            top_up,
+        )?;
+    }
+    anchor_lang::system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            anchor_lang::system_program::Allocate {
+                account_to_allocate: wsol_unwrap_account.clone(),
+            },
+            signer,
+        ),
+        space as u64,
+    )?;
+    anchor_lang::system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            anchor_lang::system_program::Assign {
+                account_to_assign: wsol_unwrap_account.clone(),
+            },
+            signer,
+        ),
+        &token_program.key(),
+    )?;
+    token::initialize_account3(CpiContext::new(
+        token_program.clone(),
+        InitializeAccount3 {
+            account: wsol_unwrap_account.clone(),
+            mint: native_mint.to_account_info(),
+            authority: wsol_unwrap_account.clone(),
+        },
+    ))?;
+    token::transfer(
+        CpiContext::new(
+            token_program.clone(),
+            Transfer {
+                from: from.to_account_info(),
+                to: wsol_unwrap_account.clone(),
+                authority: user.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+    token::close_account(CpiContext::new_with_signer(
+        token_program,
+        CloseAccount {
+            account: wsol_unwrap_account.clone(),
+            destination: user.to_account_info(),
+            authority: wsol_unwrap_account.clone(),
+        },
+        signer,
+    ))?;
+
+    transfer_lamports(
+        user.to_account_info(),
+        vault_native_account.to_account_info(),
+        system_program,
+        amount,
+    )?;
+    let res = send_deposit_message(
+        ctx,
+        _NATIVE_ADDRESS.to_string(),
+        user_key,
+        amount,
+        to,
+        data,
+    )?;
+    Ok(res)
+}
+
+pub fn deposit_and_swap<'info>(
+    ctx: Context<'_, '_, '_, 'info, DepositToken<'info>>,
+    amount: u64,
+    receiver: String,
+    minimum_receive: u128,
+    path: Vec<String>,
+) -> Result<u128> {
+    verify_network_address(&receiver)?;
+    require!(minimum_receive > 0, AssetManagerError::InvalidAmount);
+    require!(
+        !path.is_empty() && path.len() <= MAX_SWAP_PATH && path.iter().all(|token| !token.is_empty()),
+        AssetManagerError::InvalidSwapPath
+    );
+    let router = hub_contracts(&ctx.accounts.hub_contracts)?.router.clone();
+    let data = SwapData::create(receiver, minimum_receive, path).encode();
+    deposit_with_data(ctx, amount, router, data)
+}
+
+pub fn deposit_to_loans<'info>(
+    ctx: Context<'_, '_, '_, 'info, DepositToken<'info>>,
+    amount: u64,
+    amount_to_borrow: u128,
+) -> Result<u128> {
+    let loans = hub_contracts(&ctx.accounts.hub_contracts)?.loans.clone();
+    let data = LoansDepositData::create(amount_to_borrow).encode();
+    deposit_with_data(ctx, amount, loans, data)
+}
+
+fn hub_contracts<'a>(hub_contracts: &'a Option<Account<HubContracts>>) -> Result<&'a HubContracts> {
+    let hub_contracts = hub_contracts
+        .as_ref()
+        .ok_or(AssetManagerError::HubContractsNotConfigured)?;
+    require!(
+        !hub_contracts.router.is_empty() && !hub_contracts.loans.is_empty(),
+        AssetManagerError::HubContractsNotConfigured
+    );
+    Ok(&**hub_contracts)
+}
+
+// native sol is deposited when no token account is passed, wsol follows the wrapped native mode
+fn deposit_with_data<'info>(
+    ctx: Context<'_, '_, '_, 'info, DepositToken<'info>>,
+    amount: u64,
+    to: String,
+    data: Vec<u8>,
+) -> Result<u128> {
+    match ctx.accounts.from.as_ref().map(|from| from.mint) {
+        None => deposit_native(ctx, amount, Some(to), Some(data)),
+        Some(mint) if mint == spl_token::native_mint::ID && ctx.accounts.state.wrapped_native_mode => {
+            deposit_wrapped_native(ctx, amount, Some(to), Some(data))
+        }
+        Some(_) => deposit_token(ctx, amount, Some(to), Some(data)),
+    }
+}
+
+fn verify_network_address(address: &str) -> Result<()> {
+    require!(is_valid_network_address(address), AssetManagerError::InvalidNetworkAddress);
+    Ok(())
+}
+
+// deposits without `to` are sent to the depositor's own network address
+fn verify_to_address(to: &Option<String>) -> Result<()> {
+    if let Some(to) = to {
+        require!(is_valid_network_address(to), AssetManagerError::InvalidToAddress);
+    }
+    Ok(())
+}
+
 fn send_deposit_message<'info>(
     ctx: Context<'_, '_, '_, 'info, DepositToken<'info>>,
     token_address: String,
@@ -199,99 +632,82 @@ fn send_deposit_message<'info>(
     to: Option<String>,
     data: Option<Vec<u8>>,
 ) -> Result<u128> {
+    let xcall_config = read_config(&ctx.accounts.xcall_config, &ctx.accounts.state.xcall)?;
+    let to = to.unwrap_or_else(|| format!("{}/{}", xcall_config.network_id, from));
+    require!(
+        !to.is_empty() && to.len() <= 100,
+        AssetManagerError::InvalidToAddress
+    );
+    let sn = xcall_config.sequence_no + 1;
+    ctx.accounts.token_state.total_deposited += amount as u128;
+    if let Some(deposit_record) = ctx.accounts.deposit_record.as_mut() {
+        deposit_record.set_inner(DepositRecord {
+            sn,
+            token: ctx.accounts.token_config.token,
+            amount,
+            from,
+            to: to.clone(),
+            status: DepositStatus::Pending,
+        });
+    }
     let deposit_message = DepositMessage::create(
         token_address.clone(),
         from.to_string(),
-        to.unwrap_or("".to_string()),
+        to,
         amount,
         data.unwrap_or(vec![]),
     );
     let data = rlp::encode(&deposit_message).to_vec();
+    // the revert only points at a record when the deposit kept one
+    let record_sn = ctx.accounts.deposit_record.is_some().then_some(sn);
     let rollback = rlp::encode(&DepositRevert::create(
         token_address,
         from.to_string(),
         amount,
+        record_sn,
     ))
     .to_vec();
 
-    let sources = &ctx.accounts.xcall_manager_state.sources;
-    let destinations = &ctx.accounts.xcall_manager_state.destinations;
-    let message = AnyMessage::CallMessageWithRollback(CallMessageWithRollback { data, rollback });
-    let envelope: Envelope = Envelope::new(message, sources.clone(), destinations.clone());
-    let envelope_encoded = rlp::encode(&envelope).to_vec();
-
-    let icon_asset_manager =
-        NetworkAddress::from_str(&ctx.accounts.state.icon_asset_manager).unwrap(); //todo: get network address without unwrap
-
-    let xcall_config = &ctx.remaining_accounts[0];
-    let rollback_account = &ctx.remaining_accounts[1];
-    let sysvar_account = &ctx.remaining_accounts[2];
-    let fee_handler = &ctx.remaining_accounts[3];
-    let from_authority = &ctx.accounts.from_authority;
-    let xcall_authority = &ctx.accounts.xcall_authority;
-    // the accounts for centralized connections is contained here.
-    let remaining_accounts: &[AccountInfo<'info>] = ctx.remaining_accounts.split_at(4).1;
-    let bump = ctx.bumps.xcall_authority;
-    let seeds = &[Authority::SEED_PREFIX.as_ref(), &[bump]];
-    let signer_seeds = &[&seeds[..]];
-
-    let cpi_accounts: SendCallCtx = SendCallCtx {
-        config: xcall_config.to_account_info(),
-        rollback_account: Some(rollback_account.to_account_info()),
-        fee_handler: fee_handler.to_account_info(),
-        signer: from_authority.to_account_info(),
-        instruction_sysvar: sysvar_account.to_account_info(),
-        dapp_authority: Some(xcall_authority.to_account_info()),
-        system_program: ctx.accounts.system_program.to_account_info(),
-    };
-
-    let xcall_program = ctx.accounts.xcall.to_account_info();
-    let cpi_ctx = CpiContext::new_with_signer(xcall_program, cpi_accounts, signer_seeds)
-        .with_remaining_accounts(remaining_accounts.to_vec());
-    let result = xcall::cpi::send_call(cpi_ctx, envelope_encoded, icon_asset_manager)?;
-    Ok(result.get())
-}
-
-pub fn verify_protocols<'info>(
-    xcall_manager_program: Program<'info, XcallManager>,
-    xm_state: Account<'info, XmState>,
-    protocols: &[String],
-) -> Result<bool> {
-    let cpi_accounts: VerifyProtocols = VerifyProtocols {
-        state: xm_state.to_account_info(),
-    };
-
-    let cpi_ctx = CpiContext::new(xcall_manager_program.to_account_info(), cpi_accounts);
-    let verified = xcall_manager::cpi::verify_protocols(cpi_ctx, protocols.to_vec())?;
-    Ok(verified.get())
+    xcall_cpi::send_message_with_rollback(
+        XcallCpiAccounts {
+            xcall: ctx.accounts.xcall.to_account_info(),
+            signer: ctx.accounts.from_authority.to_account_info(),
+            dapp_authority: ctx.accounts.xcall_authority.to_account_info(),
+            dapp_authority_bump: ctx.bumps.xcall_authority,
+            system_program: ctx.accounts.system_program.to_account_info(),
+            remaining_accounts: ctx.remaining_accounts,
+        },
+        &ctx.accounts.state.icon_asset_manager,
+        data,
+        rollback,
+        &ctx.accounts.xcall_manager_state,
+    )
 }
 
 pub fn get_handle_call_message_accounts<'info>(
     ctx: Context<'_, '_, '_, 'info, GetParams<'info>>,
+    from: String,
     data: Vec<u8>,
 ) -> Result<ParamAccounts> {
     let token_address = decode_token_address(&data)?;
     let method = decode_method(&data)?;
-    if token_address != _NATIVE_ADDRESS.to_string() && method == WITHDRAW_TO {
-        Ok(ParamAccounts {
-            accounts: get_spl_token_withdraw_to_accounts(ctx, data)?,
-        })
+    let message_record = message_record_address(ctx.program_id, &from, &data);
+    let mut accounts = if token_address != _NATIVE_ADDRESS.to_string() && method == WITHDRAW_TO {
+        get_spl_token_withdraw_to_accounts(ctx, data)?
     } else if token_address != _NATIVE_ADDRESS && method == DEPOSIT_REVERT {
-        Ok(ParamAccounts {
-            accounts: get_spl_token_deposit_revert_accounts(ctx, data)?,
-        })
+        get_spl_token_deposit_revert_accounts(ctx, data)?
+    } else if token_address == _NATIVE_ADDRESS && method == WITHDRAW_TO && ctx.accounts.state.wrapped_native_mode {
+        get_wrapped_native_withdraw_to_accounts(ctx, data)?
     } else if token_address == _NATIVE_ADDRESS && (method == WITHDRAW_TO_NATIVE || method == WITHDRAW_TO) {
-        Ok(ParamAccounts {
-            accounts: get_native_token_withdraw_to_accounts(ctx, data)?,
-        })
+        get_native_token_withdraw_to_accounts(ctx, data)?
     } else if token_address == _NATIVE_ADDRESS && method == DEPOSIT_REVERT {
-        Ok(ParamAccounts {
-            accounts: get_native_token_deposit_revert_accounts(ctx, data)?,
-        })
+        get_native_token_deposit_revert_accounts(ctx, data)?
     } else {
-        let accounts: Vec<ParamAccountProps> = vec![];
-        Ok(ParamAccounts { accounts })
-    }
+        return Ok(ParamAccounts { accounts: vec![] });
+    };
+    accounts.push(ParamAccountProps::new(message_record, false));
+    accounts.push(ParamAccountProps::new_readonly(sysvar::instructions::ID, false));
+    Ok(ParamAccounts { accounts })
 }
 
 pub fn handle_call_message<'info>(
@@ -300,49 +716,60 @@ pub fn handle_call_message<'info>(
     data: Vec<u8>,
     protocols: Vec<String>,
 ) -> Result<HandleCallMessageResponse> {
+    // a second execution of a request fails the whole call instead of reporting a failure that the
+    // hub would roll back
+    record_message_request(&mut ctx.accounts)?;
+    call_response(process_call_message(ctx, from, data, protocols), is_permanent_failure)
+}
+
+fn process_call_message<'info>(
+    ctx: Context<'_, '_, '_, 'info, HandleCallMessage<'info>>,
+    from: String,
+    data: Vec<u8>,
+    protocols: Vec<String>,
+) -> Result<()> {
     require!(
         verify_protocols(
-            ctx.accounts.xcall_manager.clone(),
-            ctx.accounts.xcall_manager_state.clone(),
+            ctx.accounts.xcall_manager.to_account_info(),
+            ctx.accounts.xcall_manager_state.to_account_info(),
             &protocols
         )?,
         AssetManagerError::ProtocolMismatch
     );
 
     let token_address = decode_token_address(&data)?;
-    let result;
     if token_address != _NATIVE_ADDRESS.to_string() {
-        result = handle_token_call_message(ctx, from, data);
+        handle_token_call_message(ctx, from, data)
     } else {
-        result = handle_native_call_message(ctx, from, data);
+        handle_native_call_message(ctx, from, data)
     }
+}
 
-    match result {
-        Ok(value) => {
-            return Ok(HandleCallMessageResponse {
-                success: value,
-                message: "Success".to_owned(),
-            });
-        }
-        Err(e) => {
-            return Ok(HandleCallMessageResponse {
-                success: false,
-                message: e.to_string(),
-            });
-        }
-    }
+// errors of messages that can never execute, anything else fails the call so it can be retried
+fn is_permanent_failure(code: u32) -> bool {
+    [
+        u32::from(AssetManagerError::ProtocolMismatch),
+        u32::from(AssetManagerError::NotIconAssetManager),
+        u32::from(AssetManagerError::UnauthorizedCaller),
+        u32::from(AssetManagerError::UnknownMessage),
+        u32::from(AssetManagerError::DecoderError),
+        u32::from(AssetManagerError::NotAnAddress),
+        u32::from(AssetManagerError::InvalidAmount),
+        u32::from(SpokeError::DecoderError),
+    ]
+    .contains(&code)
 }
 
 fn handle_token_call_message<'info>(
     ctx: Context<'_, '_, '_, 'info, HandleCallMessage<'info>>,
     from: String,
     data: Vec<u8>,
-) -> Result<bool> {
+) -> Result<()> {
     let state = ctx.accounts.state.clone();
     let bump = ctx.bumps.valult_authority.unwrap();
     let method = decode_method(&data)?;
     
-    let to: &Account<'info, TokenAccount> = ctx
+    let to = ctx
         .accounts
         .to
         .as_ref()
@@ -368,12 +795,12 @@ fn handle_token_call_message<'info>(
         .valult_authority
         .as_ref()
         .ok_or(AssetManagerError::ValultAuthorityIsRequired)?;
-    let mut token_state: &mut Account<'info, TokenState> = &mut ctx.accounts.token_state;
     if method == WITHDRAW_TO {
         if from != state.icon_asset_manager{
            return Err(AssetManagerError::NotIconAssetManager.into())
         }
         let message = decode_withdraw_to_msg(&data)?;
+        let amount = u64::try_from(message.amount).map_err(|_| AssetManagerError::InvalidAmount)?;
         let token_pubkey = Pubkey::from_str(&message.token_address)
             .map_err(|_| AssetManagerError::NotAnAddress)?;
         let recipient_pubkey =
@@ -384,21 +811,23 @@ fn handle_token_call_message<'info>(
         if token_pubkey != mint.key() {
             return Err(AssetManagerError::InvalidToAddress.into())
         }
+        verify_token_config(&ctx.accounts.token_config, mint.key(), true)?;
 
-        let mut withdraw_amount = message.amount as u64;
-        let recepient_token_balance = to.amount;
-        if recepient_token_balance == 0 {
-            let admin_token_account = ctx.accounts.admin_token_account.as_ref().unwrap();
-            require!(admin_token_account.owner == state.admin, AssetManagerError::InvalidAdmin);
-            let token_account_creation_fee = ctx.accounts.token_account_creation_pda.as_ref().unwrap().token_account_creation_fee;
-            require!(
-                withdraw_amount >= token_account_creation_fee,
-                AssetManagerError::MintAmountLessThanTokenCreationFee
-            );
+        let recipient_created = create_recipient_token_account(&ctx.accounts, to, mint)?;
+        let mut withdraw_amount = amount;
+        let token_account_creation_fee = token_account_creation_fee(
+            &ctx.accounts,
+            &state,
+            recipient_created,
+            withdraw_amount,
+            false,
+            ctx.bumps.fee_pool,
+        )?;
+        if token_account_creation_fee > 0 {
             transfer_token(
                 token_account_creation_fee,
                 vault_token_account.to_account_info(),
-                admin_token_account.to_account_info(),
+                ctx.accounts.admin_token_account.as_ref().unwrap().to_account_info(),
                 mint.key(),
                 token_program.to_account_info(),
                 vault_authority.clone(),
@@ -407,7 +836,7 @@ fn handle_token_call_message<'info>(
             withdraw_amount -= token_account_creation_fee;
         }
         withdraw_token(
-            &mut token_state,
+            &mut ctx.accounts.token_state,
             vault_token_account.to_account_info(),
             to.to_account_info(),
             withdraw_amount,
@@ -416,6 +845,7 @@ fn handle_token_call_message<'info>(
             vault_authority.clone(),
             bump,
         )?;
+        ctx.accounts.token_state.total_withdrawn += u128::from(amount);
     } else if method == DEPOSIT_REVERT {
         let from_network_address = NetworkAddress::from_str(&from)?;
         if from_network_address.account() != state.xcall.to_string() {
@@ -433,22 +863,46 @@ fn handle_token_call_message<'info>(
         if token_pubkey != mint.key() {
             return Err(AssetManagerError::InvalidToAddress.into())
         }
-        
+        verify_token_config(&ctx.accounts.token_config, mint.key(), false)?;
+
+        let recipient_created = create_recipient_token_account(&ctx.accounts, to, mint)?;
+        let mut refund_amount = message.amount;
+        let token_account_creation_fee = token_account_creation_fee(
+            &ctx.accounts,
+            &state,
+            recipient_created,
+            refund_amount,
+            true,
+            ctx.bumps.fee_pool,
+        )?;
+        if token_account_creation_fee > 0 {
+            transfer_token(
+                token_account_creation_fee,
+                vault_token_account.to_account_info(),
+                ctx.accounts.admin_token_account.as_ref().unwrap().to_account_info(),
+                mint.key(),
+                token_program.to_account_info(),
+                vault_authority.clone(),
+                bump,
+            )?;
+            refund_amount -= token_account_creation_fee;
+        }
         withdraw_token(
-            &mut token_state,
+            &mut ctx.accounts.token_state,
             vault_token_account.to_account_info(),
             to.to_account_info(),
-            message.amount as u64,
+            refund_amount,
             mint.key(),
             token_program.to_account_info(),
             vault_authority.clone(),
             bump,
         )?;
+        ctx.accounts.token_state.total_reverted += u128::from(message.amount);
+        mark_deposit_reverted(&mut ctx.accounts.deposit_record, message.sn)?;
     } else {
         return Err(AssetManagerError::UnknownMessage.into());
     }
-
-    Ok(true)
+    Ok(())
 }
 
 fn transfer_token<'info>(
@@ -479,7 +933,7 @@ fn handle_native_call_message<'info>(
     ctx: Context<'_, '_, '_, 'info, HandleCallMessage<'info>>,
     from: String,
     data: Vec<u8>
-) -> Result<bool> {
+) -> Result<()> {
     require!(ctx.accounts.vault_native_account.clone().unwrap().key()==get_native_vault_pda(&ctx.program_id)?.0, AssetManagerError::InvalidValutNativeAuthority);
     let state = ctx.accounts.state.clone();
     let bump = ctx.bumps.vault_native_account.unwrap();
@@ -491,12 +945,12 @@ fn handle_native_call_message<'info>(
         .as_ref()
         .ok_or(AssetManagerError::ValultTokenAccountIsRequired)?;
     let system_program_info = ctx.accounts.system_program.to_account_info();
-    let mut token_state = &mut ctx.accounts.token_state;
     if method == WITHDRAW_TO_NATIVE || method == WITHDRAW_TO {
         if from != state.icon_asset_manager {
             return Err(AssetManagerError::NotIconAssetManager.into())
         }
         let message = decode_withdraw_to_msg(&data)?;
+        let amount = u64::try_from(message.amount).map_err(|_| AssetManagerError::InvalidAmount)?;
         let recipient_pubkey =
             Pubkey::from_str(&message.user_address).map_err(|_| AssetManagerError::NotAnAddress)?;
         if recipient_pubkey != to_native.key() {
@@ -505,14 +959,78 @@ fn handle_native_call_message<'info>(
         if message.token_address != _NATIVE_ADDRESS {
             return Err(AssetManagerError::InvalidToAddress.into())
         }
-        withdraw_native_token(
-            &mut token_state,
-            vault_native_account.clone(),
-            to_native.clone(),
-            system_program_info,
-            message.amount as u64,
-            bump,
-        )?;
+        verify_token_config(&ctx.accounts.token_config, system_program::ID, true)?;
+        ctx.accounts.token_state.total_withdrawn += u128::from(amount);
+        if method == WITHDRAW_TO && state.wrapped_native_mode {
+            let to = ctx
+                .accounts
+                .to
+                .as_ref()
+                .ok_or(AssetManagerError::InvalidToAddress)?;
+            let mint = ctx
+                .accounts
+                .mint
+                .as_ref()
+                .ok_or(AssetManagerError::MintIsRequired)?;
+            require!(mint.key() == spl_token::native_mint::ID, AssetManagerError::NotWrappedNative);
+            let token_program = ctx
+                .accounts
+                .token_program
+                .as_ref()
+                .ok_or(AssetManagerError::TokenProgramIsRequired)?;
+            let recipient_created = create_recipient_token_account(&ctx.accounts, to, mint)?;
+            let mut withdraw_amount = amount;
+            let token_account_creation_fee = token_account_creation_fee(
+                &ctx.accounts,
+                &state,
+                recipient_created,
+                withdraw_amount,
+                false,
+                ctx.bumps.fee_pool,
+            )?;
+            if token_account_creation_fee > 0 {
+                let admin_token_account = ctx.accounts.admin_token_account.as_ref().unwrap();
+                require!(admin_token_account.mint == spl_token::native_mint::ID, AssetManagerError::InvalidAdmin);
+                withdraw_native_token(
+                    &mut ctx.accounts.token_state,
+                    vault_native_account.clone(),
+                    admin_token_account.to_account_info(),
+                    system_program_info.clone(),
+                    token_account_creation_fee,
+                    bump,
+                )?;
+                token::sync_native(CpiContext::new(
+                    token_program.to_account_info(),
+                    SyncNative {
+                        account: admin_token_account.to_account_info(),
+                    },
+                ))?;
+                withdraw_amount -= token_account_creation_fee;
+            }
+            withdraw_native_token(
+                &mut ctx.accounts.token_state,
+                vault_native_account.clone(),
+                to.to_account_info(),
+                system_program_info,
+                withdraw_amount,
+                bump,
+            )?;
+            token::sync_native(CpiContext::new(
+                token_program.to_account_info(),
+                SyncNative {
+                    account: to.to_account_info(),
+                },
+            ))?;
+        } else {
+            withdraw_native_token(
+                &mut ctx.accounts.token_state,
+                vault_native_account.clone(),
+                to_native.clone(),
+                system_program_info,
+                amount,
+                bump,
+            )?;
+        }
     } else if method == DEPOSIT_REVERT {
         let from_network_address = NetworkAddress::from_str(&from)?;
         if from_network_address.account() != state.xcall.to_string() {
@@ -527,19 +1045,21 @@ fn handle_native_call_message<'info>(
         if message.token_address != _NATIVE_ADDRESS {
             return Err(AssetManagerError::InvalidToAddress.into())
         }
+        verify_token_config(&ctx.accounts.token_config, system_program::ID, false)?;
+        ctx.accounts.token_state.total_reverted += u128::from(message.amount);
         withdraw_native_token(
-            &mut token_state,
+            &mut ctx.accounts.token_state,
             vault_native_account.clone(),
             to_native.clone(),
             system_program_info,
-            message.amount as u64,
+            message.amount,
             bump,
         )?;
+        mark_deposit_reverted(&mut ctx.accounts.deposit_record, message.sn)?;
     } else {
         return Err(AssetManagerError::UnknownMessage.into());
     }
-
-    Ok(true)
+    Ok(())
 }
 
 fn withdraw_token<'info>(
@@ -583,10 +1103,11 @@ fn withdraw_native_token<'info>(
     amount: u64,
     bump: u8,
 ) -> Result<()> {
-    if amount > **vault_native_account.try_borrow_lamports()? {
+    let vault_balance = native_vault_balance(&vault_native_account)?;
+    if amount > vault_balance {
         return Err(AssetManagerError::InsufficientBalance.into())
     }
-    verify_withdraw(token_state, amount, vault_native_account.get_lamports())?;
+    verify_withdraw(token_state, amount, vault_balance)?;
 
     let seeds: &[&[u8]; 2] = &[b"vault_native".as_ref(), &[bump]];
     let signer = &[&seeds[..]];
@@ -606,6 +1127,30 @@ fn withdraw_native_token<'info>(
     Ok(())
 }
 
+// deposits sent without a record, or before records were added, carry no sequence number
+fn mark_deposit_reverted(deposit_record: &mut Option<Account<DepositRecord>>, sn: Option<u128>) -> Result<()> {
+    if let Some(sn) = sn {
+        let record = deposit_record
+            .as_mut()
+            .ok_or(AssetManagerError::DepositRecordRequired)?;
+        require!(record.sn == sn, AssetManagerError::InvalidDepositRecord);
+        record.status = DepositStatus::Reverted;
+    }
+    Ok(())
+}
+
+fn record_message_request(accounts: &mut HandleCallMessage) -> Result<()> {
+    let record = accounts.message_record.to_account_info();
+    message_record::record_request(
+        &mut accounts.message_record.requests,
+        &record,
+        &accounts.signer.to_account_info(),
+        &accounts.system_program.to_account_info(),
+        &accounts.instructions_sysvar,
+        &accounts.state.xcall,
+    )
+}
+
 pub fn verify_withdraw(token_state: &mut TokenState, amount: u64, balance: u64) -> Result<()> {
     let limit = calculate_limit(&token_state, balance)?;
     if balance.saturating_sub(amount) < limit {
@@ -618,6 +1163,102 @@ pub fn verify_withdraw(token_state: &mut TokenState, amount: u64, balance: u64)
     Ok(())
 }
 
+pub fn migrate<'info>(ctx: Context<'_, '_, '_, 'info, Migrate<'info>>) -> Result<()> {
+    let admin = ctx.accounts.admin.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+    let state_info = ctx.accounts.state.to_account_info();
+    let state_grown = grow_account(&state_info, &admin, &system_program, 8 + State::INIT_SPACE)?;
+    let mut state = State::try_deserialize(&mut &state_info.try_borrow_data()?[..])?;
+    require_keys_eq!(state.admin, admin.key(), AssetManagerError::UnauthorizedCaller);
+    if state_grown {
+        state.rollback_timeout = DEFAULT_USER_ROLLBACK_TIMEOUT;
+        state.try_serialize(&mut &mut state_info.try_borrow_mut_data()?[..])?;
+    }
+
+    let mut accounts = ctx.remaining_accounts.iter();
+    while let Some(account) = accounts.next() {
+        if account.owner == ctx.program_id
+            && account.try_borrow_data()?.starts_with(&TokenState::DISCRIMINATOR)
+        {
+            let vault = accounts.next().ok_or(AssetManagerError::InvalidMigrationAccount)?;
+            migrate_token_state(account, vault, &admin, &system_program, ctx.program_id)?;
+        } else {
+            migrate_token_account_creation_fee(account, &admin, &system_program, ctx.program_id)?;
+        }
+    }
+    Ok(())
+}
+
+// token states from before the vault counters existed start them at the vault balance, so the
+// accounting has no gap for the funds deposited before the upgrade
+fn migrate_token_state<'info>(
+    token_state_info: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    admin: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    program_id: &Pubkey,
+) -> Result<()> {
+    require!(token_state_info.is_writable, AssetManagerError::InvalidMigrationAccount);
+    if !grow_account(token_state_info, admin, system_program, 8 + TokenState::INIT_SPACE)? {
+        return Ok(());
+    }
+    let mut token_state = TokenState::try_deserialize(&mut &token_state_info.try_borrow_data()?[..])?;
+    let (token_state_address, _) =
+        Pubkey::find_program_address(&[TOKEN_STATE_SEED, token_state.token.as_ref()], program_id);
+    require_keys_eq!(token_state_info.key(), token_state_address, AssetManagerError::InvalidMigrationAccount);
+    let vault_balance = if token_state.token == system_program::ID {
+        let (vault_address, _) = Pubkey::find_program_address(&[VAULT_NATIVE_SEED], program_id);
+        require_keys_eq!(vault.key(), vault_address, AssetManagerError::InvalidMigrationAccount);
+        native_vault_balance(vault)?
+    } else {
+        let vault_token_account = TokenAccount::try_deserialize(&mut &vault.try_borrow_data()?[..])?;
+        let (vault_authority, _) =
+            Pubkey::find_program_address(&[VAULT_SEED, token_state.token.as_ref()], program_id);
+        require!(
+            vault.owner == &token::ID
+                && vault_token_account.owner == vault_authority
+                && vault_token_account.mint == token_state.token,
+            AssetManagerError::InvalidMigrationAccount
+        );
+        vault_token_account.amount
+    };
+    token_state.total_deposited = u128::from(vault_balance);
+    token_state.total_withdrawn = 0;
+    token_state.total_reverted = 0;
+    token_state.try_serialize(&mut &mut token_state_info.try_borrow_mut_data()?[..])
+}
+
+fn migrate_token_account_creation_fee<'info>(
+    fee_info: &AccountInfo<'info>,
+    admin: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    program_id: &Pubkey,
+) -> Result<()> {
+    require!(
+        fee_info.owner == program_id && fee_info.is_writable,
+        AssetManagerError::InvalidTokenAccountCreationFee
+    );
+    if !grow_account(fee_info, admin, system_program, 8 + TokenAccountCreationFee::INIT_SPACE)? {
+        return Ok(());
+    }
+    let mut fee = TokenAccountCreationFee::try_deserialize(&mut &fee_info.try_borrow_data()?[..])?;
+    let (fee_address, _) =
+        Pubkey::find_program_address(&[TOKEN_CREATION_ACCOUNT_SEED, fee.token.as_ref()], program_id);
+    require_keys_eq!(fee_info.key(), fee_address, AssetManagerError::InvalidTokenAccountCreationFee);
+    // fees configured before fee modes existed are fixed amounts
+    fee.fee_mode = TokenAccountCreationFeeMode::Fixed;
+    fee.try_serialize(&mut &mut fee_info.try_borrow_mut_data()?[..])
+}
+
+pub fn close_message_record<'info>(
+    ctx: Context<'_, '_, '_, 'info, CloseMessageRecord<'info>>,
+) -> Result<()> {
+    message_record::require_requests_closed(
+        &ctx.accounts.message_record.requests,
+        ctx.remaining_accounts,
+    )
+}
+
 pub fn force_rollback<'info>(
     ctx: Context<'_, '_, '_, 'info, ForceRollback<'info>>,
     request_id: u128,
@@ -625,29 +1266,151 @@ pub fn force_rollback<'info>(
     connection_sn: u128,
     dst_program_id: Pubkey,
 )->Result<()> {
-    let bump = ctx.bumps.xcall_authority;
-    let seeds = &[Authority::SEED_PREFIX.as_ref(), &[bump]];
-    let signer_seeds = &[&seeds[..]];
-
-    let proxy_request = &ctx.remaining_accounts[0];
-    let config = &ctx.remaining_accounts[1];
-    let admin = &ctx.remaining_accounts[2];
-
-    let remaining_accounts: &[AccountInfo<'info>] = ctx.remaining_accounts.split_at(3).1;
-    let cpi_accounts: HandleForcedRollbackCtx =  HandleForcedRollbackCtx{
-        proxy_request: proxy_request.to_account_info(),
-        signer: ctx.accounts.signer.to_account_info(),
-        dapp_authority: ctx.accounts.xcall_authority.to_account_info(),
-        config: config.to_account_info(),
-        system_program: ctx.accounts.system_program.to_account_info(),
-        admin: admin.to_account_info()
-    };
+    ctx.accounts.force_rollback(
+        ctx.bumps.xcall_authority,
+        ctx.remaining_accounts,
+        request_id,
+        source_nid,
+        connection_sn,
+        dst_program_id,
+    )
+}
 
-    let xcall_program = ctx.accounts.xcall.to_account_info();
-    let cpi_ctx = CpiContext::new_with_signer(xcall_program, cpi_accounts, signer_seeds)
-    .with_remaining_accounts(remaining_accounts.to_vec());
-        
-    let _result = xcall::cpi::handle_forced_rollback(cpi_ctx, request_id, source_nid, connection_sn, dst_program_id)?;
+pub fn set_rollback_timeout(ctx: Context<SetRollbackTimeout>, rollback_timeout: i64) -> Result<()> {
+    require!(rollback_timeout >= MIN_USER_ROLLBACK_TIMEOUT, AssetManagerError::InvalidRollbackTimeout);
+    ctx.accounts.state.rollback_timeout = rollback_timeout;
+    Ok(())
+}
+
+pub fn request_user_rollback(
+    ctx: Context<RequestUserRollback>,
+    _source_nid: String,
+    _connection_sn: u128,
+    _connection: Pubkey,
+) -> Result<()> {
+    // anyone can pay for the request, the depositor signs for it with their deposit record
+    let request = read_proxy_request(&ctx.accounts.proxy_request, &ctx.accounts.state.xcall)?;
+    require!(request.to == crate::id().to_string(), SpokeError::InvalidProxyRequest);
+    verify_depositor_message(&request.data, &ctx.accounts.deposit_record)?;
+    ctx.accounts.rollback_request.set_inner(RollbackRequest {
+        requester: ctx.accounts.requester.key(),
+        proxy_request: ctx.accounts.proxy_request.key(),
+        requested_at: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+// only withdrawals can be rolled back, by the depositor of a deposit of the same token they pay out
+fn verify_depositor_message(data: &[u8], deposit_record: &DepositRecord) -> Result<()> {
+    let method = decode_method(data)?;
+    require!(
+        method == WITHDRAW_TO || method == WITHDRAW_TO_NATIVE,
+        AssetManagerError::UnknownMessage
+    );
+    let message = decode_withdraw_to_msg(data)?;
+    require!(
+        message.user_address == deposit_record.from.to_string(),
+        AssetManagerError::NotMessageRecipient
+    );
+    let token = Pubkey::from_str(&message.token_address).map_err(|_| AssetManagerError::NotAnAddress)?;
+    require_keys_eq!(token, deposit_record.token, AssetManagerError::InvalidDepositRecord);
+    Ok(())
+}
+
+pub fn user_force_rollback<'info>(
+    ctx: Context<'_, '_, '_, 'info, UserForceRollback<'info>>,
+    request_id: u128,
+    source_nid: String,
+    connection_sn: u128,
+    dst_program_id: Pubkey,
+) -> Result<()> {
+    let request = read_proxy_request(&ctx.accounts.proxy_request, &ctx.accounts.state.xcall)?;
+    verify_depositor_message(&request.data, &ctx.accounts.deposit_record)?;
+    let rollback_request = &ctx.accounts.rollback_request;
+    require!(
+        Clock::get()?.unix_timestamp >= rollback_request.requested_at + ctx.accounts.state.rollback_timeout,
+        AssetManagerError::RollbackTimeoutActive
+    );
+    xcall_cpi::force_rollback(
+        XcallCpiAccounts {
+            xcall: ctx.accounts.xcall.to_account_info(),
+            signer: ctx.accounts.recipient.to_account_info(),
+            dapp_authority: ctx.accounts.xcall_authority.to_account_info(),
+            dapp_authority_bump: ctx.bumps.xcall_authority,
+            system_program: ctx.accounts.system_program.to_account_info(),
+            remaining_accounts: ctx.remaining_accounts,
+        },
+        ForcedRollbackAccounts {
+            proxy_request: ctx.accounts.proxy_request.to_account_info(),
+            config: ctx.accounts.xcall_config.to_account_info(),
+            admin: ctx.accounts.xcall_admin.to_account_info(),
+        },
+        request_id,
+        source_nid,
+        connection_sn,
+        dst_program_id,
+    )
+}
+
+fn create_recipient_token_account(
+    accounts: &HandleCallMessage,
+    to: &UncheckedAccount,
+    mint: &Account<Mint>,
+) -> Result<bool> {
+    creation_fee::create_recipient_token_account(RecipientTokenAccount {
+        payer: accounts.signer.to_account_info(),
+        token_account: to.to_account_info(),
+        authority: accounts.to_native.to_account_info(),
+        mint: mint.to_account_info(),
+        system_program: accounts.system_program.to_account_info(),
+        token_program: accounts
+            .token_program
+            .as_ref()
+            .ok_or(AssetManagerError::TokenProgramIsRequired)?
+            .to_account_info(),
+        associated_token_program: accounts
+            .associated_token_program
+            .as_ref()
+            .ok_or(AssetManagerError::AssociatedTokenProgramIsRequired)?
+            .to_account_info(),
+    })
+}
+
+// fee paid to the admin when the relayer had to create the recipient token account
+fn token_account_creation_fee<'info>(
+    accounts: &HandleCallMessage<'info>,
+    state: &State,
+    recipient_created: bool,
+    amount: u64,
+    is_revert: bool,
+    fee_pool_bump: Option<u8>,
+) -> Result<u64> {
+    creation_fee::token_account_creation_fee(
+        CreationFeeAccounts {
+            signer: accounts.signer.to_account_info(),
+            system_program: accounts.system_program.to_account_info(),
+            fee_pool: accounts.fee_pool.clone(),
+            fee_pool_bump,
+            fee_token_account_owner: accounts.admin_token_account.as_ref().map(|account| account.owner),
+            configured_fee: accounts
+                .token_account_creation_pda
+                .as_ref()
+                .map(|fee_account| fee_account.fee())
+                .transpose()?,
+        },
+        state.relayer_reimbursement,
+        state.admin,
+        recipient_created,
+        amount,
+        is_revert,
+        state.charge_creation_fee_on_revert,
+    )
+}
+
+// reverts only require the token to be registered so refunds still go through for disabled tokens
+fn verify_token_config(token_config: &TokenConfig, token: Pubkey, require_enabled: bool) -> Result<()> {
+    require!(token_config.token == token, AssetManagerError::TokenNotConfigured);
+    require!(!require_enabled || token_config.enabled, AssetManagerError::TokenDisabled);
     Ok(())
 }
 
@@ -655,3 +1418,24 @@ fn balance_of(account: &Account<TokenAccount>) -> Result<u64> {
     Ok(account.amount)
 }
 
+fn transfer_lamports<'info>(
+    from: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let transfer_instruction =
+        anchor_lang::solana_program::system_instruction::transfer(&from.key(), &to.key(), amount);
+    anchor_lang::solana_program::program::invoke(
+        &transfer_instruction,
+        &[from, to, system_program],
+    )?;
+    Ok(())
+}
+
+// lamports held by the native vault minus the rent exempt reserve, which is never withdrawable
+fn native_vault_balance(vault_native_account: &AccountInfo) -> Result<u64> {
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(0);
+    Ok(vault_native_account.lamports().saturating_sub(rent_exempt_minimum))
+}
+
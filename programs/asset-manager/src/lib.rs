@@ -42,6 +42,62 @@ pub mod asset_manager {
         )
     }
 
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        xcall: Option<Pubkey>,
+        icon_asset_manager: Option<String>,
+        xcall_manager: Option<Pubkey>,
+        xcall_manager_state: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::update_config(ctx, xcall, icon_asset_manager, xcall_manager, xcall_manager_state)
+    }
+
+    pub fn set_wrapped_native_mode(
+        ctx: Context<SetWrappedNativeMode>,
+        enabled: bool,
+    ) -> Result<()> {
+        instructions::set_wrapped_native_mode(ctx, enabled)
+    }
+
+    pub fn register_token(
+        ctx: Context<RegisterToken>,
+        token: Pubkey,
+        hub_asset: String,
+        decimals: u8,
+    ) -> Result<()> {
+        instructions::register_token(ctx, token, hub_asset, decimals)
+    }
+
+    pub fn update_token_config(
+        ctx: Context<UpdateTokenConfig>,
+        token: Pubkey,
+        hub_asset: String,
+        decimals: u8,
+        enabled: bool,
+    ) -> Result<()> {
+        instructions::update_token_config(ctx, token, hub_asset, decimals, enabled)
+    }
+
+    pub fn register_token_vault(
+        ctx: Context<RegisterTokenVault>,
+        period: u64,
+        percentage: u64,
+        token_account_creation_fee: u64,
+        allow_freeze_authority: bool,
+    ) -> Result<()> {
+        instructions::register_token_vault(
+            ctx,
+            period,
+            percentage,
+            token_account_creation_fee,
+            allow_freeze_authority,
+        )
+    }
+
+    pub fn list_tokens(ctx: Context<ListTokens>) -> Result<Vec<Pubkey>> {
+        instructions::list_tokens(ctx)
+    }
+
     pub fn configure_rate_limit(
         ctx: Context<ConfigureRateLimit>,
         token: Pubkey,
@@ -51,18 +107,72 @@ pub mod asset_manager {
         instructions::configure_rate_limit(ctx, token, period, percentage)
     }
 
+    pub fn set_creation_fee_policy(
+        ctx: Context<SetCreationFeePolicy>,
+        charge_on_revert: bool,
+    ) -> Result<()> {
+        instructions::set_creation_fee_policy(ctx, charge_on_revert)
+    }
+
+    pub fn set_relayer_reimbursement(
+        ctx: Context<SetRelayerReimbursement>,
+        relayer_reimbursement: RelayerReimbursement,
+    ) -> Result<()> {
+        instructions::set_relayer_reimbursement(ctx, relayer_reimbursement)
+    }
+
+    pub fn fund_fee_pool(ctx: Context<ManageFeePool>, amount: u64) -> Result<()> {
+        instructions::fund_fee_pool(ctx, amount)
+    }
+
+    pub fn drain_fee_pool(ctx: Context<ManageFeePool>, amount: u64) -> Result<()> {
+        instructions::drain_fee_pool(ctx, amount)
+    }
+
+    pub fn set_hub_contracts(
+        ctx: Context<SetHubContracts>,
+        router: String,
+        loans: String,
+    ) -> Result<()> {
+        instructions::set_hub_contracts(ctx, router, loans)
+    }
+
     pub fn set_token_account_creation_fee(
         ctx: Context<SetTokenAccountCreationFee>,
         token: Pubkey,
-        token_account_creation_fee: u64
+        token_account_creation_fee: u64,
+        fee_mode: TokenAccountCreationFeeMode,
+        tokens_per_sol: u64,
     ) -> Result<()> {
-        instructions::set_token_account_creation_fee(ctx, token, token_account_creation_fee)
+        instructions::set_token_account_creation_fee(
+            ctx,
+            token,
+            token_account_creation_fee,
+            fee_mode,
+            tokens_per_sol,
+        )
     }
 
     pub fn get_withdraw_limit(ctx: Context<GetWithdrawLimit>) -> Result<u64> {
         instructions::get_withdraw_limit(ctx)
     }
 
+    pub fn get_native_withdraw_limit(ctx: Context<GetNativeWithdrawLimit>) -> Result<u64> {
+        instructions::get_native_withdraw_limit(ctx)
+    }
+
+    pub fn get_vault_accounting(ctx: Context<GetVaultAccounting>) -> Result<VaultAccounting> {
+        instructions::get_vault_accounting(ctx)
+    }
+
+    pub fn get_native_vault_accounting(ctx: Context<GetNativeWithdrawLimit>) -> Result<VaultAccounting> {
+        instructions::get_native_vault_accounting(ctx)
+    }
+
+    pub fn sync_deposit_record(ctx: Context<SyncDepositRecord>) -> Result<DepositStatus> {
+        instructions::sync_deposit_record(ctx)
+    }
+
     pub fn deposit_native<'info>(
         ctx: Context<'_, '_, '_, 'info, DepositToken<'info>>,
         amount: u64,
@@ -83,6 +193,34 @@ pub mod asset_manager {
         instructions::deposit_token(ctx, amount, to, data)
     }
 
+    pub fn deposit_wrapped_native<'info>(
+        ctx: Context<'_, '_, '_, 'info, DepositToken<'info>>,
+        amount: u64,
+        to: Option<String>,
+        data: Option<Vec<u8>>,
+    ) -> Result<u128> {
+        // Unwrap wSOL and deposit it as SOL
+        instructions::deposit_wrapped_native(ctx, amount, to, data)
+    }
+
+    pub fn deposit_and_swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, DepositToken<'info>>,
+        amount: u64,
+        receiver: String,
+        minimum_receive: u128,
+        path: Vec<String>,
+    ) -> Result<u128> {
+        instructions::deposit_and_swap(ctx, amount, receiver, minimum_receive, path)
+    }
+
+    pub fn deposit_to_loans<'info>(
+        ctx: Context<'_, '_, '_, 'info, DepositToken<'info>>,
+        amount: u64,
+        amount_to_borrow: u128,
+    ) -> Result<u128> {
+        instructions::deposit_to_loans(ctx, amount, amount_to_borrow)
+    }
+
     pub fn handle_call_message<'info>(
         ctx: Context<'_, '_, '_, 'info, HandleCallMessage<'info>>,
         from: String,
@@ -92,6 +230,12 @@ pub mod asset_manager {
         instructions::handle_call_message(ctx, from, data, protocols)
     }
 
+    pub fn close_message_record<'info>(
+        ctx: Context<'_, '_, '_, 'info, CloseMessageRecord<'info>>,
+    ) -> Result<()> {
+        instructions::close_message_record(ctx)
+    }
+
     pub fn force_rollback<'info>(
         ctx: Context<'_, '_, '_, 'info, ForceRollback<'info>>,
         request_id: u128,
@@ -102,12 +246,39 @@ pub mod asset_manager {
         instructions::force_rollback(ctx, request_id, source_nid, connection_sn, dst_program_id)
     }
 
+    pub fn migrate<'info>(ctx: Context<'_, '_, '_, 'info, Migrate<'info>>) -> Result<()> {
+        instructions::migrate(ctx)
+    }
+
+    pub fn set_rollback_timeout(ctx: Context<SetRollbackTimeout>, rollback_timeout: i64) -> Result<()> {
+        instructions::set_rollback_timeout(ctx, rollback_timeout)
+    }
+
+    pub fn request_user_rollback(
+        ctx: Context<RequestUserRollback>,
+        source_nid: String,
+        connection_sn: u128,
+        connection: Pubkey,
+    ) -> Result<()> {
+        instructions::request_user_rollback(ctx, source_nid, connection_sn, connection)
+    }
+
+    pub fn user_force_rollback<'info>(
+        ctx: Context<'_, '_, '_, 'info, UserForceRollback<'info>>,
+        request_id: u128,
+        source_nid: String,
+        connection_sn: u128,
+        dst_program_id: Pubkey,
+    ) -> Result<()> {
+        instructions::user_force_rollback(ctx, request_id, source_nid, connection_sn, dst_program_id)
+    }
+
     pub fn query_handle_call_message_accounts<'info>(
         ctx: Context<'_, '_, '_, 'info, GetParams<'info>>,
-        _from: String,
+        from: String,
         data: Vec<u8>,
         _protocols: Vec<String>,
     ) -> Result<ParamAccounts> {
-        return instructions::get_handle_call_message_accounts(ctx, data);
+        return instructions::get_handle_call_message_accounts(ctx, from, data);
     }
 }
@@ -6,7 +6,7 @@ use crate::{
     id
 };
 use anchor_lang::{prelude::*, solana_program};
-use anchor_spl::{associated_token::{get_associated_token_address, self}, token::ID as TOKEN_PROGRAM_ID};
+use anchor_spl::{associated_token::{get_associated_token_address, self}, token::{spl_token, ID as TOKEN_PROGRAM_ID}};
 use solana_program::system_program::ID as SYSTEM_PROGRAM_ID;
 use std::str::FromStr;
 
@@ -41,6 +41,9 @@ pub fn get_spl_token_withdraw_to_accounts<'info>(
         ParamAccountProps::new(SYSTEM_PROGRAM_ID, false),
         ParamAccountProps::new(admin_token_address, false),
         ParamAccountProps::new(token_account_creation_pda, false),
+        ParamAccountProps::new_readonly(get_token_config_pda(&ctx.program_id, mint)?.0, false),
+        ParamAccountProps::new(get_fee_pool_pda(&ctx.program_id)?.0, false),
+        ParamAccountProps::new_readonly(*ctx.program_id, false),
     ];
 
     Ok(accounts)
@@ -57,6 +60,10 @@ pub fn get_spl_token_deposit_revert_accounts<'info>(
         Pubkey::from_str(&message.token_address).map_err(|_| AssetManagerError::NotAnAddress)?;
     let user_token_address = get_associated_token_address(&user_address, &mint);
     let vault_account = get_associated_token_address(&get_vault_pda(&ctx.program_id, mint)?.0, &mint);
+    let admin_token_address = get_associated_token_address(&ctx.accounts.state.admin, &mint);
+
+    let (token_account_creation_pda,_) = Pubkey::find_program_address(&[TOKEN_CREATION_ACCOUNT_SEED, mint.as_ref()], &id());
+
     let accounts: Vec<ParamAccountProps> = vec![
         ParamAccountProps::new(user_token_address, false),
         ParamAccountProps::new(user_address, false),
@@ -71,8 +78,11 @@ pub fn get_spl_token_deposit_revert_accounts<'info>(
         ParamAccountProps::new_readonly(ctx.accounts.state.xcall_manager, false),
         ParamAccountProps::new_readonly(ctx.accounts.state.xcall_manager_state, false),
         ParamAccountProps::new(SYSTEM_PROGRAM_ID, false),
-        ParamAccountProps::new(*ctx.program_id, false),
-        ParamAccountProps::new_readonly(*ctx.program_id, false),
+        ParamAccountProps::new(admin_token_address, false),
+        ParamAccountProps::new(token_account_creation_pda, false),
+        ParamAccountProps::new_readonly(get_token_config_pda(&ctx.program_id, mint)?.0, false),
+        ParamAccountProps::new(get_fee_pool_pda(&ctx.program_id)?.0, false),
+        deposit_record_account(&ctx.program_id, message.sn)?,
     ];
 
     Ok(accounts)
@@ -102,6 +112,47 @@ pub fn get_native_token_withdraw_to_accounts<'info>(
         ParamAccountProps::new(SYSTEM_PROGRAM_ID, false),
         ParamAccountProps::new(*ctx.program_id, false),
         ParamAccountProps::new_readonly(*ctx.program_id, false),
+        ParamAccountProps::new_readonly(get_token_config_pda(&ctx.program_id, native_mint)?.0, false),
+        ParamAccountProps::new(get_fee_pool_pda(&ctx.program_id)?.0, false),
+        ParamAccountProps::new_readonly(*ctx.program_id, false),
+    ];
+
+    Ok(accounts)
+}
+
+pub fn get_wrapped_native_withdraw_to_accounts<'info>(
+    ctx: Context<'_, '_, '_, 'info, GetParams<'info>>,
+    data: Vec<u8>,
+) -> Result<Vec<ParamAccountProps>> {
+    let message = decode_withdraw_to_msg(&data)?;
+    let user_address =
+        Pubkey::from_str(&message.user_address).map_err(|_| AssetManagerError::NotAnAddress)?;
+    let native_mint = Pubkey::from_str(_NATIVE_ADDRESS).map_err(|_| AssetManagerError::NotAnAddress)?;
+    let wrapped_native_mint = spl_token::native_mint::ID;
+    let user_token_address = get_associated_token_address(&user_address, &wrapped_native_mint);
+    let admin_token_address = get_associated_token_address(&ctx.accounts.state.admin, &wrapped_native_mint);
+
+    let (token_account_creation_pda,_) = Pubkey::find_program_address(&[TOKEN_CREATION_ACCOUNT_SEED, native_mint.as_ref()], &id());
+
+    let accounts: Vec<ParamAccountProps> = vec![
+        ParamAccountProps::new(user_token_address, false),
+        ParamAccountProps::new(user_address, false),
+        ParamAccountProps::new_readonly(ctx.accounts.state.key(), false),
+        ParamAccountProps::new(get_token_state_pda(&ctx.program_id, native_mint)?.0, false),
+        ParamAccountProps::new_readonly(*ctx.program_id, false),
+        ParamAccountProps::new(get_native_vault_pda(&ctx.program_id)?.0, false),
+        ParamAccountProps::new_readonly(wrapped_native_mint, false),
+        ParamAccountProps::new_readonly(*ctx.program_id, false),
+        ParamAccountProps::new(TOKEN_PROGRAM_ID, false),
+        ParamAccountProps::new_readonly(associated_token::ID, false),
+        ParamAccountProps::new_readonly(ctx.accounts.state.xcall_manager, false),
+        ParamAccountProps::new_readonly(ctx.accounts.state.xcall_manager_state, false),
+        ParamAccountProps::new(SYSTEM_PROGRAM_ID, false),
+        ParamAccountProps::new(admin_token_address, false),
+        ParamAccountProps::new(token_account_creation_pda, false),
+        ParamAccountProps::new_readonly(get_token_config_pda(&ctx.program_id, native_mint)?.0, false),
+        ParamAccountProps::new(get_fee_pool_pda(&ctx.program_id)?.0, false),
+        ParamAccountProps::new_readonly(*ctx.program_id, false),
     ];
 
     Ok(accounts)
@@ -131,11 +182,21 @@ pub fn get_native_token_deposit_revert_accounts<'info>(
         ParamAccountProps::new(SYSTEM_PROGRAM_ID, false),
         ParamAccountProps::new(*ctx.program_id, false),
         ParamAccountProps::new_readonly(*ctx.program_id, false),
+        ParamAccountProps::new_readonly(get_token_config_pda(&ctx.program_id, native_mint)?.0, false),
+        ParamAccountProps::new(get_fee_pool_pda(&ctx.program_id)?.0, false),
+        deposit_record_account(&ctx.program_id, message.sn)?,
     ];
 
     Ok(accounts)
 }
 
+fn deposit_record_account(program_id: &Pubkey, sn: Option<u128>) -> Result<ParamAccountProps> {
+    Ok(match sn {
+        Some(sn) => ParamAccountProps::new(get_deposit_record_pda(program_id, sn)?.0, false),
+        None => ParamAccountProps::new_readonly(*program_id, false),
+    })
+}
+
 pub fn get_vault_pda<'info>(
     program_id: &Pubkey,
     mint: Pubkey,
@@ -161,3 +222,29 @@ pub fn get_token_state_pda<'info>(
     let (pda, bump) = Pubkey::find_program_address(seeds, program_id);
     Ok((pda, bump))
 }
+
+pub fn get_token_config_pda<'info>(
+    program_id: &Pubkey,
+    mint: Pubkey
+) -> Result<(Pubkey, u8)> {
+    let seeds: &[&[u8]] = &[TOKEN_CONFIG_SEED, mint.as_ref()];
+    let (pda, bump) = Pubkey::find_program_address(seeds, program_id);
+    Ok((pda, bump))
+}
+
+pub fn get_fee_pool_pda<'info>(
+    program_id: &Pubkey,
+) -> Result<(Pubkey, u8)> {
+    let seeds: &[&[u8]] = &[FEE_POOL_SEED];
+    let (pda, bump) = Pubkey::find_program_address(seeds, program_id);
+    Ok((pda, bump))
+}
+
+pub fn get_deposit_record_pda<'info>(
+    program_id: &Pubkey,
+    sn: u128,
+) -> Result<(Pubkey, u8)> {
+    let seeds: &[&[u8]] = &[DEPOSIT_RECORD_SEED, &sn.to_be_bytes()];
+    let (pda, bump) = Pubkey::find_program_address(seeds, program_id);
+    Ok((pda, bump))
+}
@@ -1,24 +1,47 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, solana_program::system_program};
 use anchor_spl::{
-    associated_token,
-    token::{Mint, Token, TokenAccount},
+    associated_token::{self, get_associated_token_address},
+    token::{spl_token, Mint, Token, TokenAccount},
 };
-use xcall::program::Xcall;
 use xcall_manager::{self, program::XcallManager};
 
-use crate::errors::AssetManagerError;
+pub use balanced_spoke_lib::{
+    creation_fee::{RelayerReimbursement, TokenAccountCreationFeeMode, FEE_POOL_SEED},
+    params::{ParamAccountProps, ParamAccounts},
+};
+use balanced_spoke_lib::{
+    errors::SpokeError,
+    message_record::{message_hash, MESSAGE_RECORD_SEED},
+    user_rollback::ROLLBACK_REQUEST_SEED,
+    xcall_pda::{config_address, proxy_request_address, read_config},
+};
+
+use crate::{errors::AssetManagerError, program::AssetManager};
 pub const STATE_SEED: &'static [u8; 5] = b"state";
 pub const TOKEN_STATE_SEED: &'static [u8; 11] = b"token_state";
 pub const VAULT_SEED: &'static [u8; 5] = b"vault";
 pub const VAULT_NATIVE_SEED: &'static [u8; 12] = b"vault_native";
 pub const TOKEN_CREATION_ACCOUNT_SEED: &'static [u8; 14] = b"token_creation";
+pub const WSOL_UNWRAP_SEED: &'static [u8; 11] = b"wsol_unwrap";
+pub const TOKEN_CONFIG_SEED: &'static [u8; 12] = b"token_config";
+pub const TOKEN_REGISTRY_SEED: &'static [u8; 14] = b"token_registry";
+pub const HUB_CONTRACTS_SEED: &'static [u8; 13] = b"hub_contracts";
+pub const DEPOSIT_RECORD_SEED: &'static [u8; 14] = b"deposit_record";
+pub const MAX_REGISTERED_TOKENS: usize = 50;
 
 #[derive(Accounts)]
 pub struct Initialize<'info> {
     #[account(init, payer = admin, space = 8 + State::INIT_SPACE, seeds=[STATE_SEED], bump)]
     pub state: Account<'info, State>,
+    /// CHECK: native vault pda, funded up to the rent exempt minimum on initialize
+    #[account(mut, seeds = [VAULT_NATIVE_SEED], bump)]
+    pub vault_native_account: AccountInfo<'info>,
     #[account(mut)]
     pub admin: Signer<'info>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, AssetManager>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @AssetManagerError::OnlyUpgradeAuthority)]
+    pub program_data: Account<'info, ProgramData>,
     pub system_program: Program<'info, System>,
 }
 
@@ -30,6 +53,30 @@ pub struct SetAdmin<'info> {
     pub admin: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(mut, seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    #[account(address=state.admin @AssetManagerError::UnauthorizedCaller)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetWrappedNativeMode<'info> {
+    #[account(mut, seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    #[account(address=state.admin @AssetManagerError::UnauthorizedCaller)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetCreationFeePolicy<'info> {
+    #[account(mut, seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    #[account(address=state.admin @AssetManagerError::UnauthorizedCaller)]
+    pub admin: Signer<'info>,
+}
+
 #[derive(Accounts)]
 #[instruction(token: Pubkey)]
 pub struct ConfigureRateLimit<'info> {
@@ -45,10 +92,113 @@ pub struct ConfigureRateLimit<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+#[instruction(token: Pubkey)]
+pub struct RegisterToken<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(has_one=admin, seeds=[STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+
+    #[account(init, payer=admin, space = 8 + TokenConfig::INIT_SPACE, seeds=[TOKEN_CONFIG_SEED, token.as_ref()], bump)]
+    pub token_config: Account<'info, TokenConfig>,
+
+    #[account(init_if_needed, payer=admin, space = 8 + TokenRegistry::INIT_SPACE, seeds=[TOKEN_REGISTRY_SEED], bump)]
+    pub token_registry: Account<'info, TokenRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(token: Pubkey)]
+pub struct UpdateTokenConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(has_one=admin, seeds=[STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+
+    #[account(mut, seeds=[TOKEN_CONFIG_SEED, token.as_ref()], bump)]
+    pub token_config: Account<'info, TokenConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterTokenVault<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(has_one=admin, seeds=[STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(seeds=[TOKEN_CONFIG_SEED, mint.key().as_ref()], bump)]
+    pub token_config: Account<'info, TokenConfig>,
+
+    /// CHECK: vault authority pda, owns the vault token account
+    #[account(seeds=[VAULT_SEED, mint.key().as_ref()], bump)]
+    pub valult_authority: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        associated_token::mint = mint,
+        associated_token::authority = valult_authority
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(init, payer=admin, space = 8 + TokenState::INIT_SPACE, seeds=[TOKEN_STATE_SEED, mint.key().as_ref()], bump)]
+    pub token_state: Account<'info, TokenState>,
+
+    #[account(init, payer = admin, seeds=[TOKEN_CREATION_ACCOUNT_SEED, mint.key().as_ref()], bump, space = 8 + TokenAccountCreationFee::INIT_SPACE)]
+    pub token_account_creation_pda: Account<'info, TokenAccountCreationFee>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ListTokens<'info> {
+    #[account(seeds=[TOKEN_REGISTRY_SEED], bump)]
+    pub token_registry: Account<'info, TokenRegistry>,
+}
+
+#[derive(Accounts)]
+pub struct SetRelayerReimbursement<'info> {
+    #[account(mut, seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    #[account(address=state.admin @AssetManagerError::UnauthorizedCaller)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ManageFeePool<'info> {
+    #[account(seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    /// CHECK: fee pool pda, only holds the lamports used to reimburse relayers
+    #[account(mut, seeds = [FEE_POOL_SEED], bump)]
+    pub fee_pool: AccountInfo<'info>,
+    #[account(mut, address=state.admin @AssetManagerError::UnauthorizedCaller)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetHubContracts<'info> {
+    #[account(init_if_needed, payer = admin, seeds=[HUB_CONTRACTS_SEED], bump, space = 8 + HubContracts::INIT_SPACE)]
+    pub hub_contracts: Account<'info, HubContracts>,
+    #[account(seeds=[STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    #[account(mut, address=state.admin @AssetManagerError::UnauthorizedCaller)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 #[instruction(token: Pubkey)]
 pub struct SetTokenAccountCreationFee<'info> {
-    #[account(init, payer = admin, seeds=[TOKEN_CREATION_ACCOUNT_SEED, token.as_ref()], bump, space = 8 + TokenAccountCreationFee::INIT_SPACE)]
+    #[account(init_if_needed, payer = admin, seeds=[TOKEN_CREATION_ACCOUNT_SEED, token.as_ref()], bump, space = 8 + TokenAccountCreationFee::INIT_SPACE)]
     pub token_account_creation_pda: Account<'info, TokenAccountCreationFee>,
     #[account(mut, seeds=[STATE_SEED], bump)]
     pub state: Account<'info, State>,
@@ -83,6 +233,38 @@ pub struct GetWithdrawLimit<'info> {
     pub vault_token_account: Account<'info, TokenAccount>,
 }
 
+#[derive(Accounts)]
+pub struct GetVaultAccounting<'info> {
+    #[account(
+        seeds = [
+            TOKEN_STATE_SEED,
+            vault_token_account.mint.as_ref()
+        ],
+        bump,
+    )]
+    pub token_state: Account<'info, TokenState>,
+    #[account(constraint=vault_token_account.owner==valult_authority.key() @AssetManagerError::InvalidValultTokenAccount)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    /// CHECK: vault authority pda, owns the vault token account
+    #[account(seeds = [VAULT_SEED, vault_token_account.mint.as_ref()], bump)]
+    pub valult_authority: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetNativeWithdrawLimit<'info> {
+    #[account(
+        seeds = [
+            TOKEN_STATE_SEED,
+            system_program::ID.as_ref()
+        ],
+        bump,
+    )]
+    pub token_state: Account<'info, TokenState>,
+    /// CHECK: native vault pda, only its lamports are read
+    #[account(seeds = [VAULT_NATIVE_SEED], bump)]
+    pub vault_native_account: AccountInfo<'info>,
+}
+
 #[derive(Accounts)]
 pub struct DepositToken<'info> {
     #[account(mut)]
@@ -97,24 +279,90 @@ pub struct DepositToken<'info> {
 
     #[account(mut, seeds = [VAULT_NATIVE_SEED], bump)]
     pub vault_native_account: Option<AccountInfo<'info>>,
+    //temporary token account used to unwrap wsol deposits, closed within the instruction
+    #[account(mut, seeds = [WSOL_UNWRAP_SEED, from_authority.key().as_ref()], bump)]
+    pub wsol_unwrap_account: Option<AccountInfo<'info>>,
+    #[account(address = spl_token::native_mint::ID @AssetManagerError::NotWrappedNative)]
+    pub native_mint: Option<Account<'info, Mint>>,
     #[account(mut, seeds = [STATE_SEED], bump)]
     pub state: Account<'info, State>,
 
     #[account(constraint=xcall_manager_state.key()==state.xcall_manager_state @AssetManagerError::InvalidXcallManagerState)]
     pub xcall_manager_state: Account<'info, xcall_manager::XmState>,
 
-    pub xcall: Program<'info, Xcall>,
+    /// CHECK: the xcall set in the state, which may differ from the one the program was built against
+    #[account(
+        address = state.xcall @SpokeError::InvalidXcallProgram,
+        constraint = xcall.executable @SpokeError::InvalidXcallProgram
+    )]
+    pub xcall: UncheckedAccount<'info>,
     #[account(
       init_if_needed, payer=from_authority, space = Authority::MAX_SPACE, seeds = [Authority::SEED_PREFIX], bump
     )]
     pub xcall_authority: Account<'info, Authority>,
-    //xcall validates this account
-    //not additionally used in balanced
-    #[account(mut)]
-    pub xcall_config: Account<'info, xcall::state::Config>,
+    /// CHECK: config of the xcall set in the state
+    #[account(
+        mut,
+        address = config_address(&state.xcall) @SpokeError::InvalidXcallConfig,
+        owner = state.xcall @SpokeError::InvalidXcallConfig
+    )]
+    pub xcall_config: UncheckedAccount<'info>,
     pub xcall_manager: Program<'info, XcallManager>,
     pub token_program: Option<Program<'info, Token>>,
     pub system_program: Program<'info, System>,
+    // the handler checks `token` against the deposited mint
+    #[account(seeds=[TOKEN_CONFIG_SEED, token_config.token.as_ref()], bump)]
+    pub token_config: Account<'info, TokenConfig>,
+    #[account(mut, seeds=[TOKEN_STATE_SEED, token_config.token.as_ref()], bump)]
+    pub token_state: Account<'info, TokenState>,
+    #[account(seeds=[HUB_CONTRACTS_SEED], bump)]
+    pub hub_contracts: Option<Account<'info, HubContracts>>,
+    // keyed by the xcall sequence number the deposit is sent with, only kept for depositors that
+    // want to track the deposit on chain
+    #[account(
+        init,
+        payer = from_authority,
+        space = 8 + DepositRecord::INIT_SPACE,
+        seeds = [DEPOSIT_RECORD_SEED, &(read_config(&xcall_config, &state.xcall)?.sequence_no + 1).to_be_bytes()],
+        bump
+    )]
+    pub deposit_record: Option<Account<'info, DepositRecord>>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct DepositRecord {
+    pub sn: u128,
+    pub token: Pubkey,
+    pub amount: u64,
+    pub from: Pubkey,
+    #[max_len(100)]
+    pub to: String,
+    pub status: DepositStatus,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum DepositStatus {
+    Pending,
+    Executed,
+    Reverted,
+}
+
+#[derive(Accounts)]
+pub struct SyncDepositRecord<'info> {
+    #[account(mut, has_one = from @AssetManagerError::InvalidDepositRecord)]
+    pub deposit_record: Account<'info, DepositRecord>,
+    /// CHECK: depositor, receives the rent once the record is closed
+    #[account(mut)]
+    pub from: AccountInfo<'info>,
+    #[account(seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    /// CHECK: xcall rollback pda of the deposit, only its lamports are read
+    #[account(
+        address = Pubkey::find_program_address(&[b"rollback", &deposit_record.sn.to_be_bytes()], &state.xcall).0
+            @AssetManagerError::InvalidDepositRecord
+    )]
+    pub rollback_account: AccountInfo<'info>,
 }
 
 #[account]
@@ -126,13 +374,49 @@ pub struct State {
     pub xcall_manager: Pubkey,
     pub xcall_manager_state: Pubkey,
     pub admin: Pubkey,
+    pub wrapped_native_mode: bool,
+    pub charge_creation_fee_on_revert: bool,
+    pub relayer_reimbursement: RelayerReimbursement,
+    // seconds a message recipient waits between requesting and triggering a forced rollback
+    pub rollback_timeout: i64,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub xcall: Pubkey,
+    pub icon_asset_manager: String,
+    pub xcall_manager: Pubkey,
+    pub xcall_manager_state: Pubkey,
 }
 
+balanced_spoke_lib::token_account_creation_fee!(token: Pubkey);
+
+// fixed size fields come first so clients can filter registered tokens with getProgramAccounts
 #[account]
 #[derive(InitSpace)]
-pub struct TokenAccountCreationFee {
+pub struct TokenConfig {
     pub token: Pubkey,
-    pub token_account_creation_fee: u64
+    pub decimals: u8,
+    pub enabled: bool,
+    #[max_len(100)]
+    pub hub_asset: String,
+}
+
+// hub network addresses targeted by the typed deposit instructions
+#[account]
+#[derive(InitSpace)]
+pub struct HubContracts {
+    #[max_len(100)]
+    pub router: String,
+    #[max_len(100)]
+    pub loans: String,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct TokenRegistry {
+    #[max_len(50)]
+    pub tokens: Vec<Pubkey>,
 }
 
 #[account]
@@ -143,32 +427,50 @@ pub struct TokenState {
     pub percentage: u64,
     pub last_update: i64,
     pub current_limit: u64,
+    // cumulative amounts moved through the vault, deposited - withdrawn - reverted should match its balance
+    pub total_deposited: u128,
+    pub total_withdrawn: u128,
+    pub total_reverted: u128,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct VaultAccounting {
+    pub deposited: u128,
+    pub withdrawn: u128,
+    pub reverted: u128,
+    pub vault_balance: u64,
+    // vault balance minus the balance expected from the counters, non zero values need a look
+    pub delta: i128,
 }
 
 #[derive(Accounts)]
+#[instruction(from: String, data: Vec<u8>)]
 pub struct HandleCallMessage<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
     #[account(address=(Pubkey::find_program_address(&[b"config"], &state.xcall).0) @AssetManagerError::OnlyXcall)]
     pub xcall_singer: Signer<'info>,
+    /// CHECK: associated token account of `to_native`, created by the handler when missing
     #[account(
-        init_if_needed,
-        payer = signer,
-        associated_token::mint = mint,
-        associated_token::authority = to_native
+        mut,
+        address = get_associated_token_address(
+            &to_native.key(),
+            &mint.as_ref().ok_or(AssetManagerError::MintIsRequired)?.key()
+        ) @AssetManagerError::InvalidToAddress
     )]
-    pub to: Option<Account<'info, TokenAccount>>,
+    pub to: Option<UncheckedAccount<'info>>,
     /// CHECK: this account is validated inside instruction logic
     #[account(mut)]
     pub to_native: AccountInfo<'info>,
     #[account(seeds = [STATE_SEED], bump)]
     pub state: Account<'info, State>,
+    #[account(mut, seeds=[TOKEN_STATE_SEED, token_config.token.as_ref()], bump)]
     pub token_state: Account<'info, TokenState>,
     #[account(mut, constraint=vault_token_account.owner==valult_authority.clone().unwrap().key() @AssetManagerError::InvalidValultTokenAccount)]
     pub vault_token_account: Option<Account<'info, TokenAccount>>,
     #[account(mut, seeds = [VAULT_NATIVE_SEED], bump)]
     pub vault_native_account: Option<AccountInfo<'info>>,
-    #[account(constraint = mint.key() == token_state.token)]
+    #[account(constraint = mint.key() == token_state.token || (mint.key() == spl_token::native_mint::ID && token_state.token == system_program::ID))]
     pub mint: Option<Account<'info, Mint>>,
 
     #[account(seeds = [VAULT_SEED, mint.clone().unwrap().key().as_ref()], bump)]
@@ -181,9 +483,29 @@ pub struct HandleCallMessage<'info> {
     #[account(constraint=xcall_manager_state.key()==state.xcall_manager_state @AssetManagerError::InvalidXcallManagerState)]
     pub xcall_manager_state: Account<'info, xcall_manager::XmState>,
     pub system_program: Program<'info, System>,
+    #[account(mut)]
     pub admin_token_account: Option<Account<'info, TokenAccount>>,
     #[account(mut, seeds=[TOKEN_CREATION_ACCOUNT_SEED,token_state.token.as_ref()], bump)]
     pub token_account_creation_pda: Option<Account<'info, TokenAccountCreationFee>>,
+    // the handler checks `token` against the token of the message
+    #[account(seeds=[TOKEN_CONFIG_SEED, token_config.token.as_ref()], bump)]
+    pub token_config: Account<'info, TokenConfig>,
+    /// CHECK: fee pool pda, only required when relayers are reimbursed in lamports
+    #[account(mut, seeds = [FEE_POOL_SEED], bump)]
+    pub fee_pool: Option<AccountInfo<'info>>,
+    #[account(mut)]
+    pub deposit_record: Option<Account<'info, DepositRecord>>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = 8 + MessageRecord::INIT_SPACE,
+        seeds = [MESSAGE_RECORD_SEED, &message_hash(&from, &data)],
+        bump
+    )]
+    pub message_record: Account<'info, MessageRecord>,
+    /// CHECK: instructions sysvar, read for the xcall request being executed
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions_sysvar: AccountInfo<'info>,
 }
 
 #[derive(Accounts)]
@@ -192,57 +514,117 @@ pub struct GetParams<'info> {
     pub state: Account<'info, State>,
 }
 
+balanced_spoke_lib::force_rollback!(AssetManagerError::UnauthorizedCaller);
+
+balanced_spoke_lib::message_record!();
+balanced_spoke_lib::close_message_record!(AssetManagerError::UnauthorizedCaller);
+
+// brings the state and the `TokenAccountCreationFee` and `TokenState` accounts, passed as
+// remaining accounts, to the current layout. Each token state is followed by its vault
 #[derive(Accounts)]
-pub struct ForceRollback<'info> {
-    #[account(seeds = [STATE_SEED], bump)]
-    pub state: Account<'info, State>,
-    #[account(mut, address=state.admin @AssetManagerError::UnauthorizedCaller)]
-    pub signer: Signer<'info>,
-    pub xcall: Program<'info, Xcall>,
-    #[account(
-      init_if_needed, payer=signer, space = Authority::MAX_SPACE, seeds = [Authority::SEED_PREFIX], bump
-    )]
-    pub xcall_authority: Account<'info, Authority>,
+pub struct Migrate<'info> {
+    /// CHECK: resized before it is deserialized, the admin is checked in the handler
+    #[account(mut, seeds = [STATE_SEED], bump)]
+    pub state: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
     pub system_program: Program<'info, System>,
-    
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Debug)]
-pub struct ParamAccountProps {
-    pub pubkey: Pubkey,
-    pub is_writable: bool,
-    pub is_signer: bool,
+#[derive(Accounts)]
+pub struct SetRollbackTimeout<'info> {
+    #[account(mut, seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    #[account(address=state.admin @AssetManagerError::UnauthorizedCaller)]
+    pub admin: Signer<'info>,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Debug)]
-pub struct ParamAccounts {
-    pub accounts: Vec<ParamAccountProps>,
+#[derive(Accounts)]
+#[instruction(source_nid: String, connection_sn: u128, connection: Pubkey)]
+pub struct RequestUserRollback<'info> {
+    #[account(seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    #[account(mut)]
+    pub requester: Signer<'info>,
+    pub from: Signer<'info>,
+    #[account(has_one = from @AssetManagerError::InvalidDepositRecord)]
+    pub deposit_record: Account<'info, DepositRecord>,
+    /// CHECK: xcall proxy request of the message, owner and layout are checked in the instruction
+    #[account(
+        address = proxy_request_address(&state.xcall, &source_nid, connection_sn, &connection)
+            @SpokeError::InvalidProxyRequest
+    )]
+    pub proxy_request: AccountInfo<'info>,
+    #[account(
+        init,
+        payer = requester,
+        space = 8 + RollbackRequest::INIT_SPACE,
+        seeds = [ROLLBACK_REQUEST_SEED, proxy_request.key().as_ref()],
+        bump
+    )]
+    pub rollback_request: Account<'info, RollbackRequest>,
+    pub system_program: Program<'info, System>,
 }
 
-impl ParamAccountProps {
-    pub fn new(pubkey: Pubkey, is_signer: bool) -> Self {
-        Self {
-            pubkey,
-            is_signer,
-            is_writable: true,
-        }
-    }
-
-    pub fn new_readonly(pubkey: Pubkey, is_signer: bool) -> Self {
-        Self {
-            pubkey,
-            is_signer,
-            is_writable: false,
-        }
-    }
+#[derive(Accounts)]
+#[instruction(request_id: u128, source_nid: String, connection_sn: u128, dst_program_id: Pubkey)]
+pub struct UserForceRollback<'info> {
+    #[account(seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+    #[account(
+        constraint = deposit_record.from == recipient.key() @AssetManagerError::InvalidDepositRecord
+    )]
+    pub deposit_record: Account<'info, DepositRecord>,
+    /// CHECK: paid for the rollback request and gets its rent back
+    #[account(mut)]
+    pub requester: AccountInfo<'info>,
+    #[account(
+        mut,
+        close = requester,
+        has_one = requester @SpokeError::InvalidRollbackRequest,
+        seeds = [ROLLBACK_REQUEST_SEED, proxy_request.key().as_ref()],
+        bump
+    )]
+    pub rollback_request: Account<'info, RollbackRequest>,
+    /// CHECK: xcall proxy request of the message, checked against the xcall pda
+    #[account(
+        mut,
+        address = proxy_request_address(&state.xcall, &source_nid, connection_sn, &dst_program_id)
+            @SpokeError::InvalidProxyRequest
+    )]
+    pub proxy_request: AccountInfo<'info>,
+    /// CHECK: config of the xcall set in the state
+    #[account(
+        mut,
+        address = config_address(&state.xcall) @SpokeError::InvalidXcallConfig,
+        owner = state.xcall @SpokeError::InvalidXcallConfig
+    )]
+    pub xcall_config: UncheckedAccount<'info>,
+    /// CHECK: xcall admin, checked against the xcall config before the rollback
+    #[account(mut)]
+    pub xcall_admin: AccountInfo<'info>,
+    /// CHECK: the xcall set in the state, which may differ from the one the program was built against
+    #[account(
+        address = state.xcall @SpokeError::InvalidXcallProgram,
+        constraint = xcall.executable @SpokeError::InvalidXcallProgram
+    )]
+    pub xcall: UncheckedAccount<'info>,
+    #[account(
+      init_if_needed, payer=recipient, space = Authority::MAX_SPACE, seeds = [Authority::SEED_PREFIX], bump
+    )]
+    pub xcall_authority: Account<'info, Authority>,
+    pub system_program: Program<'info, System>,
 }
 
+// start of the timeout after which the recipient of a failed inbound message can force its rollback
 #[account]
-pub struct Authority {
-    pub bump: u8,
+#[derive(InitSpace)]
+pub struct RollbackRequest {
+    pub requester: Pubkey,
+    pub proxy_request: Pubkey,
+    pub requested_at: i64,
 }
 
-impl Authority {
-    pub const SEED_PREFIX: &'static [u8; 14] = b"dapp_authority";
-    pub const MAX_SPACE: usize = 8 + 1;
-}
+balanced_spoke_lib::dapp_authority!();
@@ -6,6 +6,8 @@ pub struct DepositRevert {
     pub token_address: String,
     pub account: String,
     pub amount: u64,
+    // xcall sequence number of the reverted deposit, absent for deposits sent before it was added
+    pub sn: Option<u128>,
 }
 
 pub const DEPOSIT_REVERT: &str = "DepositRevert";
@@ -13,20 +15,24 @@ pub const DEPOSIT_REVERT: &str = "DepositRevert";
 // impl Encodable and Decodable for DepositRevert
 impl Encodable for DepositRevert {
     fn rlp_append(&self, s: &mut RlpStream) {
-        s.begin_list(4);
+        s.begin_list(if self.sn.is_some() { 5 } else { 4 });
         s.append(&DEPOSIT_REVERT);
         s.append(&self.token_address);
         s.append(&self.account);
         s.append(&self.amount);
+        if let Some(sn) = self.sn {
+            s.append(&sn);
+        }
     }
 }
 
 impl DepositRevert {
-    pub fn create(token_address: String, account: String, amount: u64) -> Self {
+    pub fn create(token_address: String, account: String, amount: u64, sn: Option<u128>) -> Self {
         Self {
             token_address,
             account,
             amount,
+            sn,
         }
     }
 }
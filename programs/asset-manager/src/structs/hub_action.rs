@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+use rlp::{Encodable, RlpStream};
+
+// `data` payloads understood by the hub contracts a deposit is forwarded to
+pub const SWAP: &str = "_swap";
+pub const LOANS_DEPOSIT: &str = "_deposit";
+pub const SWAP_ROUTE_ACTION: u8 = 1;
+pub const MAX_SWAP_PATH: usize = 4;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SwapData {
+    pub receiver: String,
+    pub minimum_receive: u128,
+    pub path: Vec<String>,
+}
+
+impl Encodable for SwapData {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(4);
+        s.append(&SWAP);
+        s.append(&self.receiver);
+        s.append(&self.minimum_receive);
+        s.begin_list(self.path.len());
+        for token in &self.path {
+            s.begin_list(2);
+            s.append(&SWAP_ROUTE_ACTION);
+            s.append(token);
+        }
+    }
+}
+
+impl SwapData {
+    pub fn create(receiver: String, minimum_receive: u128, path: Vec<String>) -> Self {
+        Self {
+            receiver,
+            minimum_receive,
+            path,
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        rlp::encode(&self.clone()).to_vec()
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LoansDepositData {
+    pub amount_to_borrow: u128,
+}
+
+impl Encodable for LoansDepositData {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(2);
+        s.append(&LOANS_DEPOSIT);
+        s.append(&self.amount_to_borrow);
+    }
+}
+
+impl LoansDepositData {
+    pub fn create(amount_to_borrow: u128) -> Self {
+        Self { amount_to_borrow }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        rlp::encode(&self.clone()).to_vec()
+    }
+}
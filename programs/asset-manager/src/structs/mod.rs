@@ -1,3 +1,4 @@
 pub mod deposit_message;
 pub mod deposit_revert;
+pub mod hub_action;
 pub mod withdraw_message;
@@ -33,8 +33,22 @@ pub enum BalancedDollarError {
     InvalidToAddress,
     #[msg["Invalid Admin"]]
     InvalidAdmin,
-    #[msg("Mint Amount Less than Token Creation Fee")]
-    MintAmountLessThanTokenCreationFee,
+    #[msg("Execution allowlist is full")]
+    ExecutionAllowlistFull,
+    #[msg("Only the program upgrade authority can initialize")]
+    OnlyUpgradeAuthority,
+    #[msg("Only the recipient of the message can request a rollback")]
+    NotMessageRecipient,
+    #[msg("Rollback timeout has not passed")]
+    RollbackTimeoutActive,
+    #[msg("Invalid rollback timeout")]
+    InvalidRollbackTimeout,
+    #[msg("Invalid mint authority")]
+    InvalidMintAuthority,
+    #[msg("Mint authority transfer is still timelocked")]
+    MintAuthorityTimelockActive,
+    #[msg("bnUSD mint is already set")]
+    MintAlreadySet,
 }
 
 impl From<DecoderError> for BalancedDollarError {
@@ -1,25 +1,31 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{token::{self, Burn, MintTo}, associated_token::get_associated_token_address};
+use anchor_spl::{
+    associated_token::get_associated_token_address,
+    token::{self, Burn},
+};
+use balanced_spoke_lib::{
+    call_data::{decode_call_data, execute_call},
+    call_response::call_response,
+    codec::{decode_cross_transfer, decode_cross_transfer_revert, decode_method},
+    errors::SpokeError,
+    message_record::{self, message_record_address},
+    migration::grow_account,
+    messages::{
+        cross_transfer::{CrossTransferMsg, CROSS_TRANSFER},
+        cross_transfer_revert::{CrossTransferRevert, CROSS_TRANSFER_REVERT},
+    },
+    network::{account_from_network_address, is_valid_network_address},
+    user_rollback::{read_proxy_request, DEFAULT_USER_ROLLBACK_TIMEOUT, MIN_USER_ROLLBACK_TIMEOUT},
+    xcall_cpi::{self, verify_protocols, ForcedRollbackAccounts, XcallCpiAccounts},
+};
 
 use crate::errors::BalancedDollarError;
 use std::str::FromStr;
-use xcall::cpi::accounts::{HandleForcedRollbackCtx, SendCallCtx};
-use xcall_lib::message::{
-    call_message_rollback::CallMessageWithRollback, envelope::Envelope, AnyMessage,
-};
 use xcall_lib::network_address::NetworkAddress;
 use xcall_lib::xcall_dapp_type::HandleCallMessageResponse;
-use xcall_manager::{cpi::accounts::VerifyProtocols, program::XcallManager, XmState};
 
 use crate::param_accounts::get_accounts;
-use crate::{
-    helpers::*,
-    states::*,
-    structs::{
-        cross_transfer::{CrossTransferMsg, CROSS_TRANSFER},
-        cross_transfer_revert::{CrossTransferRevert, CROSS_TRANSFER_REVERT},
-    },
-};
+use crate::states::*;
 
 pub fn initialize(
     ctx: Context<Initialize>,
@@ -29,6 +35,7 @@ pub fn initialize(
     bn_usd_token: Pubkey,
     xcall_manager_state: Pubkey,
 ) -> Result<()> {
+    require!(is_valid_network_address(&icon_bn_usd), BalancedDollarError::InvalidNetworkAddress);
     let state = &mut ctx.accounts.state;
     state.xcall = xcall;
     state.icon_bn_usd = icon_bn_usd;
@@ -36,6 +43,7 @@ pub fn initialize(
     state.xcall_manager_state = xcall_manager_state;
     state.bn_usd_token = bn_usd_token;
     state.admin = ctx.accounts.admin.key();
+    state.rollback_timeout = DEFAULT_USER_ROLLBACK_TIMEOUT;
     Ok(())
 }
 
@@ -47,12 +55,105 @@ pub fn set_admin(
     return  Ok(());
 }
 
+pub fn update_config(
+    ctx: Context<UpdateConfig>,
+    xcall: Option<Pubkey>,
+    icon_bn_usd: Option<String>,
+    xcall_manager: Option<Pubkey>,
+    xcall_manager_state: Option<Pubkey>,
+) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    if let Some(xcall) = xcall {
+        state.xcall = xcall;
+    }
+    if let Some(icon_bn_usd) = icon_bn_usd {
+        require!(is_valid_network_address(&icon_bn_usd), BalancedDollarError::InvalidNetworkAddress);
+        state.icon_bn_usd = icon_bn_usd;
+    }
+    if let Some(xcall_manager) = xcall_manager {
+        state.xcall_manager = xcall_manager;
+    }
+    if let Some(xcall_manager_state) = xcall_manager_state {
+        state.xcall_manager_state = xcall_manager_state;
+    }
+    emit!(ConfigUpdated {
+        xcall: state.xcall,
+        icon_bn_usd: state.icon_bn_usd.clone(),
+        xcall_manager: state.xcall_manager,
+        xcall_manager_state: state.xcall_manager_state,
+    });
+    Ok(())
+}
+
+pub fn create_spoke_mint(
+    ctx: Context<CreateSpokeMint>,
+    freeze_authority: bool,
+    name: String,
+    symbol: String,
+    uri: String,
+) -> Result<()> {
+    let bump = ctx.bumps.mint_authority;
+    let seeds = &[AUTHORITY_SEED.as_ref(), &[bump]];
+    ctx.accounts.create_metadata(&[&seeds[..]], freeze_authority, name, symbol, uri)?;
+    ctx.accounts.state.bn_usd_token = ctx.accounts.mint.key();
+    Ok(())
+}
+
+pub fn transfer_mint_authority(ctx: Context<TransferMintAuthority>, new_authority: Pubkey) -> Result<()> {
+    let (mint_authority, _) = Pubkey::find_program_address(&[AUTHORITY_SEED], ctx.program_id);
+    let mint = ctx.accounts.state.bn_usd_token;
+    ctx.accounts.mint_authority_transfer.propose(mint, new_authority, mint_authority)
+}
+
+pub fn cancel_mint_authority_transfer(ctx: Context<CancelMintAuthorityTransfer>) -> Result<()> {
+    ctx.accounts.mint_authority_transfer.cancel();
+    Ok(())
+}
+
+pub fn accept_mint_authority(ctx: Context<AcceptMintAuthority>) -> Result<()> {
+    let bump = ctx.bumps.mint_authority;
+    let seeds = &[AUTHORITY_SEED.as_ref(), &[bump]];
+    ctx.accounts.hand_over(&[&seeds[..]])
+}
+
+pub fn get_supply(ctx: Context<GetSupply>) -> Result<SupplyReport> {
+    let state = &ctx.accounts.state;
+    Ok(SupplyReport {
+        minted: state.total_minted,
+        burned: state.total_burned,
+        reverted: state.total_reverted,
+        supply: ctx.accounts.mint.supply,
+    })
+}
+
+pub fn set_creation_fee_policy(
+    ctx: Context<SetCreationFeePolicy>,
+    charge_on_revert: bool) -> Result<()>{
+    let state: &mut Account<State> = &mut ctx.accounts.state;
+    state.charge_creation_fee_on_revert = charge_on_revert;
+    return  Ok(());
+}
+
+pub fn set_relayer_reimbursement(
+    ctx: Context<SetRelayerReimbursement>,
+    relayer_reimbursement: RelayerReimbursement) -> Result<()>{
+    let state: &mut Account<State> = &mut ctx.accounts.state;
+    state.relayer_reimbursement = relayer_reimbursement;
+    return  Ok(());
+}
+
+balanced_spoke_lib::token_spoke_handlers!(BalancedDollarError);
+
 pub fn set_token_creation_fee(
     ctx: Context<SetTokenCreationFee>,
     token_creation_fee: u64,
+    fee_mode: TokenAccountCreationFeeMode,
+    tokens_per_sol: u64,
 ) -> Result<()> {
     let token_creation_account = &mut ctx.accounts.token_account_creation_pda;
     token_creation_account.token_account_creation_fee = token_creation_fee;
+    token_creation_account.fee_mode = fee_mode;
+    token_creation_account.tokens_per_sol = tokens_per_sol;
     return  Ok(());
 }
 
@@ -63,7 +164,9 @@ pub fn cross_transfer<'info>(
     data: Option<Vec<u8>>,
 ) -> Result<u128> {
     require!(icon_bnusd_value > 0, BalancedDollarError::InvalidAmount);
-    let mut value = (icon_bnusd_value / 10_u128.pow(9)) as u64;
+    require!(is_valid_network_address(&to), BalancedDollarError::InvalidToAddress);
+    let mut value = u64::try_from(icon_bnusd_value / 10_u128.pow(9))
+        .map_err(|_| BalancedDollarError::InvalidAmount)?;
     if icon_bnusd_value % 10_u128.pow(9) > 0 {
         value += 1;
     }
@@ -84,55 +187,56 @@ pub fn cross_transfer<'info>(
         },
     );
     token::burn(burn_ctx, value)?;
+    ctx.accounts.state.total_burned += value as u128;
     send_message(ctx, to, icon_bnusd_value, data)
 }
 
+// the hub bnUSD forwards the transfer to the spoke named in `to`
+pub fn cross_transfer_to<'info>(
+    ctx: Context<'_, '_, '_, 'info, CrossTransfer<'info>>,
+    network_id: String,
+    address: String,
+    icon_bnusd_value: u128,
+    data: Option<Vec<u8>>,
+) -> Result<u128> {
+    require!(
+        !network_id.is_empty() && !network_id.contains('/'),
+        BalancedDollarError::InvalidNetworkAddress
+    );
+    require!(
+        !address.is_empty() && !address.contains('/'),
+        BalancedDollarError::InvalidNetworkAddress
+    );
+    let to = format!("{}/{}", network_id, address);
+    cross_transfer(ctx, to, icon_bnusd_value, data)
+}
+
 fn send_message <'info>(
     ctx: Context<'_, '_, '_, 'info, CrossTransfer<'info>>,
     to: String,
     value: u128,
     data: Option<Vec<u8>>,
 ) -> Result<u128> {
+    let to_nid = to.split_once('/').map_or("", |(nid, _)| nid).to_string();
     let message: Vec<u8> =
         CrossTransferMsg::create(ctx.accounts.from_authority.key().to_string(), to, value, data.unwrap_or(vec![]))
             .encode();
     let rollback_message =
-        CrossTransferRevert::create(ctx.accounts.from_authority.key().to_string(), value).encode();
-    let sources = &ctx.accounts.xcall_manager_state.sources;
-    let destinations = &ctx.accounts.xcall_manager_state.destinations;
-    let message = AnyMessage::CallMessageWithRollback(CallMessageWithRollback {
-        data: message,
-        rollback: rollback_message,
-    });
-    let envelope: Envelope = Envelope::new(message, sources.clone(), destinations.clone());
-    let envelope_encoded = rlp::encode(&envelope).to_vec();
-
-    let icon_bn_usd = NetworkAddress::from_str(&ctx.accounts.state.icon_bn_usd)?;
-    let xcall_config = &ctx.remaining_accounts[0];
-    let rollback_account = &ctx.remaining_accounts[1];
-    let sysvar_account = &ctx.remaining_accounts[2];
-    let fee_handler = &ctx.remaining_accounts[3];
-    // the accounts for centralized connections is contained here.
-    let remaining_accounts = ctx.remaining_accounts.split_at(4).1;
-    let xcall_authority = &ctx.accounts.xcall_authority;
-    let cpi_accounts: SendCallCtx = SendCallCtx {
-        config: xcall_config.to_account_info(),
-        rollback_account: Some(rollback_account.to_account_info()),
-        fee_handler: fee_handler.to_account_info(),
-        signer: ctx.accounts.from_authority.to_account_info(),
-        instruction_sysvar: sysvar_account.to_account_info(),
-        dapp_authority: Some(xcall_authority.to_account_info()),
-        system_program: ctx.accounts.system_program.to_account_info(),
-    };
-    let bump = ctx.bumps.xcall_authority;
-    let seeds = &[Authority::SEED_PREFIX.as_ref(), &[bump]];
-    let signer_seeds = &[&seeds[..]];
-    let xcall_program = ctx.accounts.xcall.to_account_info();
-    let cpi_ctx = CpiContext::new_with_signer(xcall_program, cpi_accounts, signer_seeds)
-         .with_remaining_accounts(remaining_accounts.to_vec());
-    let result = xcall::cpi::send_call(cpi_ctx, envelope_encoded, icon_bn_usd)?;
-    Ok(result.get())
-
+        CrossTransferRevert::create(ctx.accounts.from_authority.key().to_string(), value, to_nid).encode();
+    xcall_cpi::send_message_with_rollback(
+        XcallCpiAccounts {
+            xcall: ctx.accounts.xcall.to_account_info(),
+            signer: ctx.accounts.from_authority.to_account_info(),
+            dapp_authority: ctx.accounts.xcall_authority.to_account_info(),
+            dapp_authority_bump: ctx.bumps.xcall_authority,
+            system_program: ctx.accounts.system_program.to_account_info(),
+            remaining_accounts: ctx.remaining_accounts,
+        },
+        &ctx.accounts.state.icon_bn_usd,
+        message,
+        rollback_message,
+        &ctx.accounts.xcall_manager_state,
+    )
 }
 
 pub fn handle_call_message<'info>(
@@ -141,16 +245,25 @@ pub fn handle_call_message<'info>(
     data: Vec<u8>,
     protocols: Vec<String>,
 ) -> Result<HandleCallMessageResponse> {
+    // a second execution of a request fails the whole call instead of reporting a failure that the
+    // hub would roll back
+    ctx.accounts.record_message_request()?;
+    call_response(process_call_message(ctx, from, data, protocols), is_permanent_failure)
+}
+
+fn process_call_message<'info>(
+    ctx: Context<'_, '_, '_, 'info, HandleCallMessage<'info>>,
+    from: String,
+    data: Vec<u8>,
+    protocols: Vec<String>,
+) -> Result<()> {
     let state: Account<'info, State> = ctx.accounts.state.clone();
     if !verify_protocols(
-        &ctx.accounts.xcall_manager,
-        &ctx.accounts.xcall_manager_state,
+        ctx.accounts.xcall_manager.to_account_info(),
+        ctx.accounts.xcall_manager_state.to_account_info(),
         &protocols,
     )? {
-        return Ok(HandleCallMessageResponse {
-            success: false,
-            message: BalancedDollarError::InvalidProtocols.to_string(),
-        });
+        return Err(BalancedDollarError::InvalidProtocols.into());
     }
     let to_authority = ctx.accounts.to_authority.key();
 
@@ -160,10 +273,7 @@ pub fn handle_call_message<'info>(
     let method = decode_method(&data)?;
     if method == CROSS_TRANSFER {
         if from != state.icon_bn_usd {
-            return Ok(HandleCallMessageResponse {
-                success: false,
-                message: BalancedDollarError::InvalidSender.to_string(),
-            });
+            return Err(BalancedDollarError::InvalidSender.into());
         }
         let message = decode_cross_transfer(&data)?;
         let recipient_pubkey = Pubkey::from_str(account_from_network_address(message.to)?.as_str())
@@ -171,46 +281,25 @@ pub fn handle_call_message<'info>(
         if recipient_pubkey != to_authority {
             return Err(BalancedDollarError::InvalidToAddress.into());
         }
-        let mut mint_amount = translate_incoming_amount(message.value);
-        let recepient_token_balance = ctx.accounts.to.amount;
-
-        if recepient_token_balance == 0 {
-            let token_account_creation_fee = ctx.accounts.token_account_creation_pda.token_account_creation_fee;
-            require!(ctx.accounts.admin_token_account.owner == state.admin, BalancedDollarError::InvalidAdmin);
-            require!(
-                mint_amount >= token_account_creation_fee,
-                BalancedDollarError::MintAmountLessThanTokenCreationFee
-            );
-            mint(
-                ctx.accounts.mint.to_account_info(),
-                ctx.accounts.admin_token_account.to_account_info(),
-                ctx.accounts.mint_authority.to_account_info(),
-                ctx.accounts.token_program.to_account_info(),
-                token_account_creation_fee,
-                signer,
-            )?;
-
-            mint_amount -= token_account_creation_fee;
-        }
-        mint(
-            ctx.accounts.mint.to_account_info(),
-            ctx.accounts.to.to_account_info(),
-            ctx.accounts.mint_authority.to_account_info(),
-            ctx.accounts.token_program.to_account_info(),
-            mint_amount,
-            signer,
-        )?;
-        return Ok(HandleCallMessageResponse {
-            success: true,
-            message: "Success".to_owned(),
+        // data that is not call data, targets a program outside the allowlist or was skipped with
+        // `skip_call_data` is ignored and the transfer is minted like a plain one
+        let allowlist = ctx.accounts.execution_allowlist.as_ref();
+        let call_skipped = ctx.accounts.message_record.call_skipped;
+        let call = decode_call_data(&message.data).ok().filter(|call| {
+            !call_skipped
+                && allowlist.map_or(false, |allowlist| allowlist.programs.contains(&call.program))
         });
+        let mint_amount = translate_incoming_amount(message.value)?;
+        ctx.accounts.mint_to_recipient(mint_amount, false, ctx.bumps.fee_pool, signer)?;
+        ctx.accounts.state.total_minted += mint_amount as u128;
+        if let Some(call) = call {
+            execute_call(&call, ctx.remaining_accounts)?;
+        }
+        return Ok(());
     } else if method == CROSS_TRANSFER_REVERT {
         let from_network_address = NetworkAddress::from_str(&from)?;
         if from_network_address.account() != state.xcall.to_string() {
-            return Ok(HandleCallMessageResponse {
-                success: false,
-                message: BalancedDollarError::InvalidSender.to_string(),
-            });
+            return Err(BalancedDollarError::InvalidSender.into());
         }
         let message = decode_cross_transfer_revert(&data)?;
         let recipient_pubkey =
@@ -218,64 +307,22 @@ pub fn handle_call_message<'info>(
         if recipient_pubkey != to_authority {
             return Err(BalancedDollarError::InvalidToAddress.into());
         }
-        mint(
-            ctx.accounts.mint.to_account_info(),
-            ctx.accounts.to.to_account_info(),
-            ctx.accounts.mint_authority.to_account_info(),
-            ctx.accounts.token_program.to_account_info(),
-            translate_incoming_amount(message.amount),
-            signer,
-        )?;
-        return Ok(HandleCallMessageResponse {
-            success: true,
-            message: "Success".to_owned(),
-        });
+        let mint_amount = translate_incoming_amount(message.amount)?;
+        ctx.accounts.mint_to_recipient(mint_amount, true, ctx.bumps.fee_pool, signer)?;
+        ctx.accounts.state.total_reverted += mint_amount as u128;
+        return Ok(());
     } else {
-        return Ok(HandleCallMessageResponse {
-            success: false,
-            message: BalancedDollarError::UnknownMessageType.to_string(),
-        });
+        return Err(BalancedDollarError::UnknownMessageType.into());
     }
 }
 
-fn mint<'info>(
-    mint: AccountInfo<'info>,
-    to: AccountInfo<'info>,
-    authority: AccountInfo<'info>,
-    program: AccountInfo<'info>,
-    amount: u64,
-    signer: &[&[&[u8]]],
-) -> Result<()> {
-    let cpi_accounts = MintTo {
-        mint,
-        to,
-        authority,
-    };
-
-    let cpi_ctx = CpiContext::new_with_signer(program, cpi_accounts, signer);
-    token::mint_to(cpi_ctx, amount)?;
-    Ok(())
-}
-
-pub fn verify_protocols<'info>(
-    xcall_manager_program: &Program<'info, XcallManager>,
-    xm_state: &Account<'info, XmState>,
-    protocols: &[String],
-) -> Result<bool> {
-    let cpi_accounts: VerifyProtocols = VerifyProtocols {
-        state: xm_state.to_account_info(),
-    };
-
-    let cpi_ctx = CpiContext::new(xcall_manager_program.to_account_info(), cpi_accounts);
-    let verified = xcall_manager::cpi::verify_protocols(cpi_ctx, protocols.to_vec())?;
-    Ok(verified.get())
-}
-
 pub fn get_handle_call_message_accounts<'info>(
     ctx: Context<'_, '_, '_, 'info, GetParams<'info>>,
+    from: String,
     data: Vec<u8>,
 ) -> Result<ParamAccounts> {
     let method = decode_method(&data)?;
+    let message_record = message_record_address(ctx.program_id, &from, &data);
     if method == CROSS_TRANSFER {
         let message: CrossTransferMsg = decode_cross_transfer(&data)?;
 
@@ -286,8 +333,10 @@ pub fn get_handle_call_message_accounts<'info>(
         let admin_token_address =
             get_associated_token_address(&ctx.accounts.state.admin, &ctx.accounts.state.bn_usd_token);
 
+        let call = decode_call_data(&message.data).ok();
+
         Ok(ParamAccounts {
-            accounts: get_accounts(ctx, user_address, user_token_address, admin_token_address)?,
+            accounts: get_accounts(ctx, user_address, user_token_address, admin_token_address, call, message_record)?,
         })
     } else if method == CROSS_TRANSFER_REVERT {
         let message = decode_cross_transfer_revert(&data)?;
@@ -299,7 +348,7 @@ pub fn get_handle_call_message_accounts<'info>(
         get_associated_token_address(&ctx.accounts.state.admin, &ctx.accounts.state.bn_usd_token);
 
         Ok(ParamAccounts {
-            accounts: get_accounts(ctx, user_address, user_token_address, admin_token_address)?,
+            accounts: get_accounts(ctx, user_address, user_token_address, admin_token_address, None, message_record)?,
         })
     } else {
         let accounts: Vec<ParamAccountProps> = vec![];
@@ -307,50 +356,131 @@ pub fn get_handle_call_message_accounts<'info>(
     }
 }
 
+pub fn migrate(ctx: Context<Migrate>) -> Result<()> {
+    let admin = ctx.accounts.admin.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+    let state_info = ctx.accounts.state.to_account_info();
+    let state_grown = grow_account(&state_info, &admin, &system_program, 8 + State::INIT_SPACE)?;
+    let mut state = State::try_deserialize(&mut &state_info.try_borrow_data()?[..])?;
+    require_keys_eq!(state.admin, admin.key(), BalancedDollarError::OnlyAdmin);
+    if state_grown {
+        state.rollback_timeout = DEFAULT_USER_ROLLBACK_TIMEOUT;
+        state.try_serialize(&mut &mut state_info.try_borrow_mut_data()?[..])?;
+    }
+
+    let fee_info = ctx.accounts.token_account_creation_pda.to_account_info();
+    if grow_account(&fee_info, &admin, &system_program, 8 + TokenAccountCreationFee::INIT_SPACE)? {
+        let mut fee = TokenAccountCreationFee::try_deserialize(&mut &fee_info.try_borrow_data()?[..])?;
+        // fees configured before fee modes existed are fixed amounts
+        fee.fee_mode = TokenAccountCreationFeeMode::Fixed;
+        fee.try_serialize(&mut &mut fee_info.try_borrow_mut_data()?[..])?;
+    }
+    Ok(())
+}
+
+pub fn close_message_record<'info>(
+    ctx: Context<'_, '_, '_, 'info, CloseMessageRecord<'info>>,
+) -> Result<()> {
+    message_record::require_requests_closed(
+        &ctx.accounts.message_record.requests,
+        ctx.remaining_accounts,
+    )
+}
+
 pub fn force_rollback<'info>(
     ctx: Context<'_, '_, '_, 'info, ForceRollback<'info>>,
     request_id: u128,
     source_nid: String,
     connection_sn: u128,
     dst_program_id: Pubkey,
-
 )->Result<()> {
-    let bump = ctx.bumps.xcall_authority;
-    let seeds = &[Authority::SEED_PREFIX.as_ref(), &[bump]];
-    let signer_seeds = &[&seeds[..]];
-
-    let proxy_request = &ctx.remaining_accounts[0];
-    let config = &ctx.remaining_accounts[1];
-    let admin = &ctx.remaining_accounts[2];
-
-    let remaining_accounts: &[AccountInfo<'info>] = ctx.remaining_accounts.split_at(3).1;
-    let cpi_accounts: HandleForcedRollbackCtx =  HandleForcedRollbackCtx{
-        proxy_request: proxy_request.to_account_info(),
-        signer: ctx.accounts.signer.to_account_info(),
-        dapp_authority: ctx.accounts.xcall_authority.to_account_info(),
-        config: config.to_account_info(),
-        system_program: ctx.accounts.system_program.to_account_info(),
-        admin: admin.to_account_info()
-    };
-
-    let xcall_program = ctx.accounts.xcall.to_account_info();
-    let cpi_ctx = CpiContext::new_with_signer(xcall_program, cpi_accounts, signer_seeds)
-    .with_remaining_accounts(remaining_accounts.to_vec());
-        
-    let _result = xcall::cpi::handle_forced_rollback(cpi_ctx, request_id, source_nid, connection_sn, dst_program_id)?;
+    ctx.accounts.force_rollback(
+        ctx.bumps.xcall_authority,
+        ctx.remaining_accounts,
+        request_id,
+        source_nid,
+        connection_sn,
+        dst_program_id,
+    )
+}
+
+pub fn set_rollback_timeout(ctx: Context<SetRollbackTimeout>, rollback_timeout: i64) -> Result<()> {
+    require!(rollback_timeout >= MIN_USER_ROLLBACK_TIMEOUT, BalancedDollarError::InvalidRollbackTimeout);
+    ctx.accounts.state.rollback_timeout = rollback_timeout;
     Ok(())
 }
 
-pub fn account_from_network_address(string_network_address: String) -> Result<String> {
-    let parts = string_network_address.split('/').collect::<Vec<&str>>();
-    require!(parts.len() == 2, BalancedDollarError::InvalidNetworkAddress);
-    Ok(parts[1].to_string())
+pub fn request_user_rollback(
+    ctx: Context<RequestUserRollback>,
+    _source_nid: String,
+    _connection_sn: u128,
+    _connection: Pubkey,
+) -> Result<()> {
+    // anyone can start the timeout as soon as the message is seen failing, so it is not held back
+    // until its recipient notices
+    let request = read_proxy_request(&ctx.accounts.proxy_request, &ctx.accounts.state.xcall)?;
+    require!(request.to == crate::id().to_string(), SpokeError::InvalidProxyRequest);
+    message_recipient(&request.data)?;
+    ctx.accounts.rollback_request.set_inner(RollbackRequest {
+        requester: ctx.accounts.requester.key(),
+        proxy_request: ctx.accounts.proxy_request.key(),
+        requested_at: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+// only cross transfers can be rolled back by their recipient
+fn message_recipient(data: &[u8]) -> Result<String> {
+    if decode_method(data)? == CROSS_TRANSFER {
+        account_from_network_address(decode_cross_transfer(data)?.to)
+    } else {
+        Err(BalancedDollarError::UnknownMessageType.into())
+    }
+}
+
+pub fn user_force_rollback<'info>(
+    ctx: Context<'_, '_, '_, 'info, UserForceRollback<'info>>,
+    request_id: u128,
+    source_nid: String,
+    connection_sn: u128,
+    dst_program_id: Pubkey,
+) -> Result<()> {
+    let request = read_proxy_request(&ctx.accounts.proxy_request, &ctx.accounts.state.xcall)?;
+    require!(
+        message_recipient(&request.data)? == ctx.accounts.recipient.key().to_string(),
+        BalancedDollarError::NotMessageRecipient
+    );
+    let rollback_request = &ctx.accounts.rollback_request;
+    require!(
+        Clock::get()?.unix_timestamp >= rollback_request.requested_at + ctx.accounts.state.rollback_timeout,
+        BalancedDollarError::RollbackTimeoutActive
+    );
+    xcall_cpi::force_rollback(
+        XcallCpiAccounts {
+            xcall: ctx.accounts.xcall.to_account_info(),
+            signer: ctx.accounts.recipient.to_account_info(),
+            dapp_authority: ctx.accounts.xcall_authority.to_account_info(),
+            dapp_authority_bump: ctx.bumps.xcall_authority,
+            system_program: ctx.accounts.system_program.to_account_info(),
+            remaining_accounts: ctx.remaining_accounts,
+        },
+        ForcedRollbackAccounts {
+            proxy_request: ctx.accounts.proxy_request.to_account_info(),
+            config: ctx.accounts.xcall_config.to_account_info(),
+            admin: ctx.accounts.xcall_admin.to_account_info(),
+        },
+        request_id,
+        source_nid,
+        connection_sn,
+        dst_program_id,
+    )
 }
 
 pub fn translate_outgoing_amount(amount: u64) -> u128 {
     (amount as u128) * 10_u64.pow(9) as u128
 }
 
-pub fn translate_incoming_amount(amount: u128) -> u64 {
-    (amount / 10_u64.pow(9) as u128) as u64
+pub fn translate_incoming_amount(amount: u128) -> Result<u64> {
+    u64::try_from(amount / 10_u64.pow(9) as u128)
+        .map_err(|_| BalancedDollarError::InvalidAmount.into())
 }
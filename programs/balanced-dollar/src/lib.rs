@@ -1,10 +1,8 @@
 use anchor_lang::prelude::*;
 pub mod errors;
-pub mod helpers;
 pub mod instructions;
 pub mod param_accounts;
 pub mod states;
-pub mod structs;
 use states::*;
 use xcall_lib::xcall_dapp_type::HandleCallMessageResponse;
 
@@ -42,13 +40,96 @@ pub mod balanced_dollar {
         )
     }
 
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        xcall: Option<Pubkey>,
+        icon_bn_usd: Option<String>,
+        xcall_manager: Option<Pubkey>,
+        xcall_manager_state: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::update_config(ctx, xcall, icon_bn_usd, xcall_manager, xcall_manager_state)
+    }
+
+    pub fn create_spoke_mint(
+        ctx: Context<CreateSpokeMint>,
+        freeze_authority: bool,
+        name: String,
+        symbol: String,
+        uri: String,
+    ) -> Result<()> {
+        instructions::create_spoke_mint(ctx, freeze_authority, name, symbol, uri)
+    }
+
+    pub fn transfer_mint_authority(
+        ctx: Context<TransferMintAuthority>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        instructions::transfer_mint_authority(ctx, new_authority)
+    }
+
+    pub fn cancel_mint_authority_transfer(ctx: Context<CancelMintAuthorityTransfer>) -> Result<()> {
+        instructions::cancel_mint_authority_transfer(ctx)
+    }
+
+    pub fn accept_mint_authority(ctx: Context<AcceptMintAuthority>) -> Result<()> {
+        instructions::accept_mint_authority(ctx)
+    }
+
+    pub fn get_supply(ctx: Context<GetSupply>) -> Result<SupplyReport> {
+        instructions::get_supply(ctx)
+    }
+
+    pub fn set_creation_fee_policy(
+        ctx: Context<SetCreationFeePolicy>,
+        charge_on_revert: bool,
+    ) -> Result<()> {
+        instructions::set_creation_fee_policy(ctx, charge_on_revert)
+    }
+
+    pub fn set_relayer_reimbursement(
+        ctx: Context<SetRelayerReimbursement>,
+        relayer_reimbursement: RelayerReimbursement,
+    ) -> Result<()> {
+        instructions::set_relayer_reimbursement(ctx, relayer_reimbursement)
+    }
+
+    pub fn fund_fee_pool(ctx: Context<ManageFeePool>, amount: u64) -> Result<()> {
+        instructions::fund_fee_pool(ctx, amount)
+    }
+
+    pub fn drain_fee_pool(ctx: Context<ManageFeePool>, amount: u64) -> Result<()> {
+        instructions::drain_fee_pool(ctx, amount)
+    }
+
+    pub fn add_allowed_program(
+        ctx: Context<ConfigureExecutionAllowlist>,
+        program: Pubkey,
+    ) -> Result<()> {
+        instructions::add_allowed_program(ctx, program)
+    }
+
+    pub fn remove_allowed_program(
+        ctx: Context<ConfigureExecutionAllowlist>,
+        program: Pubkey,
+    ) -> Result<()> {
+        instructions::remove_allowed_program(ctx, program)
+    }
+
+    pub fn skip_call_data(ctx: Context<SkipCallData>, from: String, data: Vec<u8>) -> Result<()> {
+        instructions::skip_call_data(ctx, from, data)
+    }
+
     pub fn set_token_creation_fee(
         ctx: Context<SetTokenCreationFee>,
         token_creation_fee: u64,
+        fee_mode: TokenAccountCreationFeeMode,
+        tokens_per_sol: u64,
     ) -> Result<()> {
         instructions::set_token_creation_fee(
             ctx,
-            token_creation_fee
+            token_creation_fee,
+            fee_mode,
+            tokens_per_sol
         )
     }
 
@@ -61,6 +142,16 @@ pub mod balanced_dollar {
         instructions::cross_transfer(ctx, to,icon_bnusd_value, data)
     }
 
+    pub fn cross_transfer_to<'info>(
+        ctx: Context<'_, '_, '_, 'info, CrossTransfer<'info>>,
+        network_id: String,
+        address: String,
+        icon_bnusd_value: u128,
+        data: Option<Vec<u8>>,
+    ) -> Result<u128> {
+        instructions::cross_transfer_to(ctx, network_id, address, icon_bnusd_value, data)
+    }
+
     pub fn handle_call_message<'info>(
         ctx: Context<'_, '_, '_, 'info, HandleCallMessage<'info>>,
         from: String,
@@ -70,6 +161,12 @@ pub mod balanced_dollar {
         instructions::handle_call_message(ctx, from, data, protocols)
     }
 
+    pub fn close_message_record<'info>(
+        ctx: Context<'_, '_, '_, 'info, CloseMessageRecord<'info>>,
+    ) -> Result<()> {
+        instructions::close_message_record(ctx)
+    }
+
     pub fn force_rollback<'info>(
         ctx: Context<'_, '_, '_, 'info, ForceRollback<'info>>,
         request_id: u128,
@@ -80,12 +177,39 @@ pub mod balanced_dollar {
         instructions::force_rollback(ctx, request_id, source_nid, connection_sn, dst_program_id)
     }
 
+    pub fn migrate(ctx: Context<Migrate>) -> Result<()> {
+        instructions::migrate(ctx)
+    }
+
+    pub fn set_rollback_timeout(ctx: Context<SetRollbackTimeout>, rollback_timeout: i64) -> Result<()> {
+        instructions::set_rollback_timeout(ctx, rollback_timeout)
+    }
+
+    pub fn request_user_rollback(
+        ctx: Context<RequestUserRollback>,
+        source_nid: String,
+        connection_sn: u128,
+        connection: Pubkey,
+    ) -> Result<()> {
+        instructions::request_user_rollback(ctx, source_nid, connection_sn, connection)
+    }
+
+    pub fn user_force_rollback<'info>(
+        ctx: Context<'_, '_, '_, 'info, UserForceRollback<'info>>,
+        request_id: u128,
+        source_nid: String,
+        connection_sn: u128,
+        dst_program_id: Pubkey,
+    ) -> Result<()> {
+        instructions::user_force_rollback(ctx, request_id, source_nid, connection_sn, dst_program_id)
+    }
+
     pub fn query_handle_call_message_accounts<'info>(
         ctx: Context<'_, '_, '_, 'info, GetParams<'info>>,
-        _from: String,
+        from: String,
         data: Vec<u8>,
         _protocols: Vec<String>,
     ) -> Result<ParamAccounts> {
-        return instructions::get_handle_call_message_accounts(ctx, data);
+        return instructions::get_handle_call_message_accounts(ctx, from, data);
     }
 }
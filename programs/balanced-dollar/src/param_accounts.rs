@@ -1,17 +1,21 @@
 use crate::{id, states::*};
-use anchor_lang::{prelude::*, solana_program::system_program};
+use anchor_lang::{prelude::*, solana_program::{system_program, sysvar}};
 use anchor_spl::{associated_token, token::ID as TOKEN_PROGRAM_ID};
+use balanced_spoke_lib::call_data::CallData;
 
 pub fn get_accounts<'info>(
     ctx: Context<'_, '_, '_, 'info, GetParams<'info>>,
     to_authority: Pubkey,
     to: Pubkey,
     admin_token_account: Pubkey,
+    call: Option<CallData>,
+    message_record: Pubkey,
 ) -> Result<Vec<ParamAccountProps>> {
 
     let (token_account_creation_pda,_) = Pubkey::find_program_address(&[TOKEN_CREATION_ACCOUNT_SEED], &id());
+    let (fee_pool,_) = Pubkey::find_program_address(&[FEE_POOL_SEED], &id());
 
-    let accounts: Vec<ParamAccountProps> = vec![
+    let mut accounts: Vec<ParamAccountProps> = vec![
         ParamAccountProps::new(ctx.accounts.state.key(), false),
         ParamAccountProps::new(to, false),
         ParamAccountProps::new_readonly(to_authority, false),
@@ -25,7 +29,25 @@ pub fn get_accounts<'info>(
         ParamAccountProps::new_readonly(system_program::id(), false),
         ParamAccountProps::new(admin_token_account, false),
         ParamAccountProps::new(token_account_creation_pda, false),
+        ParamAccountProps::new(fee_pool, false),
     ];
+    let execution_allowlist = match call {
+        Some(_) => Pubkey::find_program_address(&[EXECUTION_ALLOWLIST_SEED], &id()).0,
+        None => id(),
+    };
+    accounts.push(ParamAccountProps::new_readonly(execution_allowlist, false));
+    accounts.push(ParamAccountProps::new(message_record, false));
+    accounts.push(ParamAccountProps::new_readonly(sysvar::instructions::ID, false));
+    if let Some(call) = call {
+        for account in call.accounts {
+            accounts.push(if account.is_writable {
+                ParamAccountProps::new(account.pubkey, false)
+            } else {
+                ParamAccountProps::new_readonly(account.pubkey, false)
+            });
+        }
+        accounts.push(ParamAccountProps::new_readonly(call.program, false));
+    }
     Ok(accounts)
 }
 
@@ -1,15 +1,28 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    associated_token,
+    associated_token::{self, get_associated_token_address},
+    metadata::Metadata,
     token::{Mint, Token, TokenAccount},
 };
-use xcall::program::Xcall;
 use xcall_manager::{self, program::XcallManager};
 
-use crate::errors::BalancedDollarError;
+pub use balanced_spoke_lib::{
+    creation_fee::{RelayerReimbursement, TokenAccountCreationFeeMode, FEE_POOL_SEED},
+    params::{ParamAccountProps, ParamAccounts},
+    token_spoke::{EXECUTION_ALLOWLIST_SEED, MINT_AUTHORITY_TRANSFER_SEED},
+};
+use balanced_spoke_lib::{
+    errors::SpokeError,
+    message_record::{message_hash, MESSAGE_RECORD_SEED},
+    user_rollback::ROLLBACK_REQUEST_SEED,
+    xcall_pda::{config_address, proxy_request_address},
+};
+
+use crate::{errors::BalancedDollarError, program::BalancedDollar};
 pub const STATE_SEED: &'static [u8; 5] = b"state";
 pub const AUTHORITY_SEED: &'static [u8; 15] = b"bnusd_authority";
 pub const TOKEN_CREATION_ACCOUNT_SEED: &'static [u8; 14] = b"token_creation";
+pub const BN_USD_DECIMALS: u8 = 9;
 
 #[derive(Accounts)]
 pub struct Initialize<'info> {
@@ -17,6 +30,10 @@ pub struct Initialize<'info> {
     pub state: Account<'info, State>,
     #[account(mut)]
     pub admin: Signer<'info>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, BalancedDollar>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @BalancedDollarError::OnlyUpgradeAuthority)]
+    pub program_data: Account<'info, ProgramData>,
     pub system_program: Program<'info, System>,
 }
 
@@ -28,9 +45,115 @@ pub struct SetAdmin<'info> {
     pub admin: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct CreateSpokeMint<'info> {
+    #[account(mut, address=state.admin @BalancedDollarError::OnlyAdmin)]
+    pub admin: Signer<'info>,
+    // the mint is only created for deployments initialized without one, it is never replaced
+    #[account(
+        mut,
+        seeds=[STATE_SEED],
+        bump,
+        constraint = state.bn_usd_token == Pubkey::default() @BalancedDollarError::MintAlreadySet
+    )]
+    pub state: Account<'info, State>,
+    #[account(
+        init,
+        payer = admin,
+        mint::decimals = BN_USD_DECIMALS,
+        mint::authority = mint_authority,
+        mint::freeze_authority = mint_authority
+    )]
+    pub mint: Account<'info, Mint>,
+    /// CHECK: program pda that holds the mint and metadata update authority
+    #[account(seeds=[AUTHORITY_SEED], bump)]
+    pub mint_authority: AccountInfo<'info>,
+    /// CHECK: metadata pda, created and validated by the token metadata program
+    #[account(
+        mut,
+        seeds=[b"metadata", token_metadata_program.key().as_ref(), mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub metadata: AccountInfo<'info>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct TransferMintAuthority<'info> {
+    #[account(init_if_needed, payer = admin, seeds=[MINT_AUTHORITY_TRANSFER_SEED], bump, space = 8 + MintAuthorityTransfer::INIT_SPACE)]
+    pub mint_authority_transfer: Account<'info, MintAuthorityTransfer>,
+    #[account(seeds=[STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    #[account(mut, address=state.admin @BalancedDollarError::OnlyAdmin)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelMintAuthorityTransfer<'info> {
+    #[account(mut, close = admin, seeds=[MINT_AUTHORITY_TRANSFER_SEED], bump)]
+    pub mint_authority_transfer: Account<'info, MintAuthorityTransfer>,
+    #[account(seeds=[STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    #[account(mut, address=state.admin @BalancedDollarError::OnlyAdmin)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptMintAuthority<'info> {
+    #[account(
+        mut,
+        close = admin,
+        seeds=[MINT_AUTHORITY_TRANSFER_SEED],
+        bump,
+        has_one = new_authority @BalancedDollarError::InvalidMintAuthority
+    )]
+    pub mint_authority_transfer: Account<'info, MintAuthorityTransfer>,
+    #[account(seeds=[STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    /// CHECK: receives the rent of the closed transfer account
+    #[account(mut, address=state.admin @BalancedDollarError::OnlyAdmin)]
+    pub admin: AccountInfo<'info>,
+    pub new_authority: Signer<'info>,
+    #[account(mut, address=state.bn_usd_token)]
+    pub mint: Account<'info, Mint>,
+    /// CHECK: program pda that currently holds the mint authority
+    #[account(seeds=[AUTHORITY_SEED], bump)]
+    pub mint_authority: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(mut, seeds=[STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    #[account(address=state.admin @BalancedDollarError::OnlyAdmin)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetCreationFeePolicy<'info> {
+    #[account(mut, seeds=[STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    #[account(address=state.admin @BalancedDollarError::OnlyAdmin)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetRelayerReimbursement<'info> {
+    #[account(mut, seeds=[STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    #[account(address=state.admin @BalancedDollarError::OnlyAdmin)]
+    pub admin: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct SetTokenCreationFee<'info> {
-    #[account(init, payer = admin, seeds=[TOKEN_CREATION_ACCOUNT_SEED], bump, space = 8 + TokenAccountCreationFee::INIT_SPACE)]
+    #[account(init_if_needed, payer = admin, seeds=[TOKEN_CREATION_ACCOUNT_SEED], bump, space = 8 + TokenAccountCreationFee::INIT_SPACE)]
     pub token_account_creation_pda: Account<'info, TokenAccountCreationFee>,
     #[account(mut, seeds=[STATE_SEED], bump)]
     pub state: Account<'info, State>,
@@ -53,20 +176,29 @@ pub struct CrossTransfer<'info> {
 
     #[account(constraint=xcall_manager_state.key() ==state.xcall_manager_state @BalancedDollarError::InvalidXcallManagerState)]
     pub xcall_manager_state: Account<'info, xcall_manager::XmState>,
-    //xcall validates this account
-    //not additionally used in balanced
-    #[account(mut)]
-    pub xcall_config: Account<'info, xcall::state::Config>,
+    /// CHECK: config of the xcall set in the state
+    #[account(
+        mut,
+        address = config_address(&state.xcall) @SpokeError::InvalidXcallConfig,
+        owner = state.xcall @SpokeError::InvalidXcallConfig
+    )]
+    pub xcall_config: UncheckedAccount<'info>,
     #[account(
         init_if_needed, payer=from_authority, space = Authority::MAX_SPACE, seeds = [Authority::SEED_PREFIX], bump
       )]
     pub xcall_authority: Account<'info, Authority>,
-    pub xcall: Program<'info, Xcall>,
+    /// CHECK: the xcall set in the state, which may differ from the one the program was built against
+    #[account(
+        address = state.xcall @SpokeError::InvalidXcallProgram,
+        constraint = xcall.executable @SpokeError::InvalidXcallProgram
+    )]
+    pub xcall: UncheckedAccount<'info>,
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
+#[instruction(from: String, data: Vec<u8>)]
 pub struct HandleCallMessage<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
@@ -74,13 +206,12 @@ pub struct HandleCallMessage<'info> {
     pub xcall_singer: Signer<'info>,
     #[account(mut, seeds=[STATE_SEED], bump)]
     pub state: Account<'info, State>,
+    /// CHECK: associated token account of `to_authority`, created by the handler when missing
     #[account(
-        init_if_needed,
-        payer = signer,
-        associated_token::mint = mint,
-        associated_token::authority = to_authority
+        mut,
+        address = get_associated_token_address(&to_authority.key(), &mint.key()) @BalancedDollarError::InvalidToAddress
     )]
-    pub to: Account<'info, TokenAccount>,
+    pub to: UncheckedAccount<'info>,
 
     /// CHECK: this account is validated inside instruction logic
     pub to_authority: AccountInfo<'info>,
@@ -94,7 +225,12 @@ pub struct HandleCallMessage<'info> {
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
     pub xcall_manager: Program<'info, XcallManager>,
-    pub xcall: Program<'info, Xcall>,
+    /// CHECK: the xcall set in the state, which may differ from the one the program was built against
+    #[account(
+        address = state.xcall @SpokeError::InvalidXcallProgram,
+        constraint = xcall.executable @SpokeError::InvalidXcallProgram
+    )]
+    pub xcall: UncheckedAccount<'info>,
 
     #[account(constraint=xcall_manager_state.key()==state.xcall_manager_state @BalancedDollarError::InvalidXcallManagerState)]
     pub xcall_manager_state: Account<'info, xcall_manager::XmState>,
@@ -103,6 +239,22 @@ pub struct HandleCallMessage<'info> {
     pub admin_token_account: Account<'info, TokenAccount>,
     #[account(mut, seeds=[TOKEN_CREATION_ACCOUNT_SEED], bump)]
     pub token_account_creation_pda: Account<'info, TokenAccountCreationFee>,
+    /// CHECK: fee pool pda, only required when relayers are reimbursed in lamports
+    #[account(mut, seeds=[FEE_POOL_SEED], bump)]
+    pub fee_pool: Option<AccountInfo<'info>>,
+    #[account(seeds=[EXECUTION_ALLOWLIST_SEED], bump)]
+    pub execution_allowlist: Option<Account<'info, ExecutionAllowlist>>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = 8 + MessageRecord::INIT_SPACE,
+        seeds = [MESSAGE_RECORD_SEED, &message_hash(&from, &data)],
+        bump
+    )]
+    pub message_record: Account<'info, MessageRecord>,
+    /// CHECK: instructions sysvar, read for the xcall request being executed
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions_sysvar: AccountInfo<'info>,
 }
 
 #[account]
@@ -115,71 +267,156 @@ pub struct State {
     pub xcall_manager: Pubkey,
     pub bn_usd_token: Pubkey,
     pub xcall_manager_state: Pubkey,
+    pub charge_creation_fee_on_revert: bool,
+    pub relayer_reimbursement: RelayerReimbursement,
+    // cumulative amounts in bnUSD base units, minted + reverted - burned should match the hub ledger
+    pub total_minted: u128,
+    pub total_burned: u128,
+    pub total_reverted: u128,
+    // seconds a message recipient waits between requesting and triggering a forced rollback
+    pub rollback_timeout: i64,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub xcall: Pubkey,
+    pub icon_bn_usd: String,
+    pub xcall_manager: Pubkey,
+    pub xcall_manager_state: Pubkey,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct SupplyReport {
+    pub minted: u128,
+    pub burned: u128,
+    pub reverted: u128,
+    pub supply: u64,
 }
 
 #[derive(Accounts)]
-pub struct GetParams<'info> {
+pub struct GetSupply<'info> {
     #[account(seeds=[STATE_SEED], bump)]
     pub state: Account<'info, State>,
+    #[account(address=state.bn_usd_token @BalancedDollarError::NotBalancedDollar)]
+    pub mint: Account<'info, Mint>,
 }
 
-#[account]
-#[derive(InitSpace)]
-pub struct TokenAccountCreationFee {
-    pub token_account_creation_fee: u64
+#[derive(Accounts)]
+pub struct GetParams<'info> {
+    #[account(seeds=[STATE_SEED], bump)]
+    pub state: Account<'info, State>,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Debug)]
-pub struct ParamAccountProps {
-    pub pubkey: Pubkey,
-    pub is_writable: bool,
-    pub is_signer: bool,
+balanced_spoke_lib::token_account_creation_fee!();
+
+balanced_spoke_lib::token_spoke_accounts!(BalancedDollarError);
+
+balanced_spoke_lib::force_rollback!(BalancedDollarError::OnlyAdmin);
+
+balanced_spoke_lib::message_record!();
+balanced_spoke_lib::close_message_record!(BalancedDollarError::OnlyAdmin);
+
+// brings the state and the token account creation fee account to the current layout
+#[derive(Accounts)]
+pub struct Migrate<'info> {
+    /// CHECK: resized before it is deserialized, the admin is checked in the handler
+    #[account(mut, seeds = [STATE_SEED], bump)]
+    pub state: UncheckedAccount<'info>,
+    /// CHECK: resized before it is deserialized
+    #[account(mut, seeds = [TOKEN_CREATION_ACCOUNT_SEED], bump)]
+    pub token_account_creation_pda: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Debug)]
-pub struct ParamAccounts {
-    pub accounts: Vec<ParamAccountProps>,
+#[derive(Accounts)]
+pub struct SetRollbackTimeout<'info> {
+    #[account(mut, seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    #[account(address=state.admin @BalancedDollarError::OnlyAdmin)]
+    pub admin: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct ForceRollback<'info> {
+#[instruction(source_nid: String, connection_sn: u128, connection: Pubkey)]
+pub struct RequestUserRollback<'info> {
     #[account(seeds = [STATE_SEED], bump)]
     pub state: Account<'info, State>,
-    #[account(mut, address=state.admin @BalancedDollarError::OnlyAdmin)]
-    pub signer: Signer<'info>,
-    pub xcall: Program<'info, Xcall>,
+    #[account(mut)]
+    pub requester: Signer<'info>,
+    /// CHECK: xcall proxy request of the message, owner and layout are checked in the instruction
     #[account(
-      init_if_needed, payer=signer, space = Authority::MAX_SPACE, seeds = [Authority::SEED_PREFIX], bump
+        address = proxy_request_address(&state.xcall, &source_nid, connection_sn, &connection)
+            @SpokeError::InvalidProxyRequest
     )]
-    pub xcall_authority: Account<'info, Authority>,
+    pub proxy_request: AccountInfo<'info>,
+    #[account(
+        init,
+        payer = requester,
+        space = 8 + RollbackRequest::INIT_SPACE,
+        seeds = [ROLLBACK_REQUEST_SEED, proxy_request.key().as_ref()],
+        bump
+    )]
+    pub rollback_request: Account<'info, RollbackRequest>,
     pub system_program: Program<'info, System>,
-    
 }
 
-impl ParamAccountProps {
-    pub fn new(pubkey: Pubkey, is_signer: bool) -> Self {
-        Self {
-            pubkey,
-            is_signer,
-            is_writable: true,
-        }
-    }
-
-    pub fn new_readonly(pubkey: Pubkey, is_signer: bool) -> Self {
-        Self {
-            pubkey,
-            is_signer,
-            is_writable: false,
-        }
-    }
+#[derive(Accounts)]
+#[instruction(request_id: u128, source_nid: String, connection_sn: u128, dst_program_id: Pubkey)]
+pub struct UserForceRollback<'info> {
+    #[account(seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+    /// CHECK: paid for the rollback request and gets its rent back
+    #[account(mut)]
+    pub requester: AccountInfo<'info>,
+    #[account(
+        mut,
+        close = requester,
+        has_one = requester @SpokeError::InvalidRollbackRequest,
+        seeds = [ROLLBACK_REQUEST_SEED, proxy_request.key().as_ref()],
+        bump
+    )]
+    pub rollback_request: Account<'info, RollbackRequest>,
+    /// CHECK: xcall proxy request of the message, checked against the xcall pda
+    #[account(
+        mut,
+        address = proxy_request_address(&state.xcall, &source_nid, connection_sn, &dst_program_id)
+            @SpokeError::InvalidProxyRequest
+    )]
+    pub proxy_request: AccountInfo<'info>,
+    /// CHECK: config of the xcall set in the state
+    #[account(
+        mut,
+        address = config_address(&state.xcall) @SpokeError::InvalidXcallConfig,
+        owner = state.xcall @SpokeError::InvalidXcallConfig
+    )]
+    pub xcall_config: UncheckedAccount<'info>,
+    /// CHECK: xcall admin, checked against the xcall config before the rollback
+    #[account(mut)]
+    pub xcall_admin: AccountInfo<'info>,
+    /// CHECK: the xcall set in the state, which may differ from the one the program was built against
+    #[account(
+        address = state.xcall @SpokeError::InvalidXcallProgram,
+        constraint = xcall.executable @SpokeError::InvalidXcallProgram
+    )]
+    pub xcall: UncheckedAccount<'info>,
+    #[account(
+      init_if_needed, payer=recipient, space = Authority::MAX_SPACE, seeds = [Authority::SEED_PREFIX], bump
+    )]
+    pub xcall_authority: Account<'info, Authority>,
+    pub system_program: Program<'info, System>,
 }
 
+// start of the timeout after which the recipient of a failed inbound message can force its rollback
 #[account]
-pub struct Authority {
-    pub bump: u8,
+#[derive(InitSpace)]
+pub struct RollbackRequest {
+    pub requester: Pubkey,
+    pub proxy_request: Pubkey,
+    pub requested_at: i64,
 }
 
-impl Authority {
-    pub const SEED_PREFIX: &'static [u8; 14] = b"dapp_authority";
-    pub const MAX_SPACE: usize = 8 + 1;
-}
+balanced_spoke_lib::dapp_authority!();
@@ -33,8 +33,28 @@ pub enum ContractError {
     InvalidToAddress,
     #[msg["Invalid Admin"]]
     InvalidAdmin,
-    #[msg("Mint Amount Less than Token Creation Fee")]
-    MintAmountLessThanTokenCreationFee,
+    #[msg("Execution allowlist is full")]
+    ExecutionAllowlistFull,
+    #[msg("Only the program upgrade authority can initialize")]
+    OnlyUpgradeAuthority,
+    #[msg("Token is not registered")]
+    TokenNotRegistered,
+    #[msg("Token is disabled")]
+    TokenDisabled,
+    #[msg("Token registry is full")]
+    TokenRegistryFull,
+    #[msg("Hub address is already registered")]
+    HubAddressAlreadyRegistered,
+    #[msg("Mint decimals are not supported")]
+    InvalidDecimals,
+    #[msg("Mint limit exceeded")]
+    MintLimitExceeded,
+    #[msg("Invalid mint authority")]
+    InvalidMintAuthority,
+    #[msg("Mint authority transfer is still timelocked")]
+    MintAuthorityTimelockActive,
+    #[msg("State is already migrated")]
+    AlreadyMigrated,
 }
 
 impl From<DecoderError> for ContractError {
@@ -1,40 +1,42 @@
-use anchor_lang::prelude::*;
-use anchor_spl::{token::{self, Burn, MintTo}, associated_token::get_associated_token_address};
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::{
+    associated_token::get_associated_token_address,
+    token::{self, Burn},
+};
+use balanced_spoke_lib::{
+    call_data::{decode_call_data, execute_call},
+    call_response::call_response,
+    codec::{decode_cross_transfer, decode_cross_transfer_revert, decode_method},
+    migration::grow_account,
+    message_record::{self, message_record_address},
+    messages::{
+        cross_transfer::{CrossTransferMsg, CROSS_TRANSFER},
+        cross_transfer_revert::{CrossTransferRevert, CROSS_TRANSFER_REVERT},
+    },
+    network::{account_from_network_address, is_valid_network_address},
+    xcall_cpi::{self, verify_protocols, XcallCpiAccounts},
+};
 
 use crate::errors::ContractError;
 use std::str::FromStr;
-use xcall::cpi::accounts::{HandleForcedRollbackCtx, SendCallCtx};
-use xcall_lib::message::{
-    call_message_rollback::CallMessageWithRollback, envelope::Envelope, AnyMessage,
-};
 use xcall_lib::network_address::NetworkAddress;
 use xcall_lib::xcall_dapp_type::HandleCallMessageResponse;
-use xcall_manager::{cpi::accounts::VerifyProtocols, program::XcallManager, XmState};
 
 use crate::param_accounts::get_accounts;
-use crate::{
-    helpers::*,
-    states::*,
-    structs::{
-        cross_transfer::{CrossTransferMsg, CROSS_TRANSFER},
-        cross_transfer_revert::{CrossTransferRevert, CROSS_TRANSFER_REVERT},
-    },
-};
+use crate::states::*;
+
+const HUB_DECIMALS: u8 = 18;
 
 pub fn initialize(
     ctx: Context<Initialize>,
     xcall: Pubkey,
-    icon_hub_addr: String,
     xcall_manager: Pubkey,
-    spoke_token_addr: Pubkey,
     xcall_manager_state: Pubkey,
 ) -> Result<()> {
     let state = &mut ctx.accounts.state;
     state.xcall = xcall;
-    state.icon_hub_addr = icon_hub_addr;
     state.xcall_manager = xcall_manager;
     state.xcall_manager_state = xcall_manager_state;
-    state.spoke_token_addr = spoke_token_addr;
     state.admin = ctx.accounts.admin.key();
     Ok(())
 }
@@ -47,12 +49,156 @@ pub fn set_admin(
     return  Ok(());
 }
 
+pub fn update_config(
+    ctx: Context<UpdateConfig>,
+    xcall: Option<Pubkey>,
+    xcall_manager: Option<Pubkey>,
+    xcall_manager_state: Option<Pubkey>,
+) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    if let Some(xcall) = xcall {
+        state.xcall = xcall;
+    }
+    if let Some(xcall_manager) = xcall_manager {
+        state.xcall_manager = xcall_manager;
+    }
+    if let Some(xcall_manager_state) = xcall_manager_state {
+        state.xcall_manager_state = xcall_manager_state;
+    }
+    emit!(ConfigUpdated {
+        xcall: state.xcall,
+        xcall_manager: state.xcall_manager,
+        xcall_manager_state: state.xcall_manager_state,
+    });
+    Ok(())
+}
+
+pub fn register_token(
+    ctx: Context<RegisterToken>,
+    hub_address: String,
+    period: u64,
+    limit: u64,
+) -> Result<()> {
+    require!(is_valid_network_address(&hub_address), ContractError::InvalidNetworkAddress);
+    require!(ctx.accounts.mint.decimals <= HUB_DECIMALS, ContractError::InvalidDecimals);
+    let state = &mut ctx.accounts.state;
+    require!(state.tokens.len() < MAX_SPOKE_TOKENS, ContractError::TokenRegistryFull);
+    require!(
+        state.mint_for_hub_address(&hub_address).is_none(),
+        ContractError::HubAddressAlreadyRegistered
+    );
+    let mint = ctx.accounts.mint.key();
+    state.tokens.push(TokenRoute {
+        mint,
+        hub_address: hub_address.clone(),
+    });
+
+    ctx.accounts.token_config.set_inner(TokenConfig {
+        mint,
+        decimals: ctx.accounts.mint.decimals,
+        enabled: true,
+        mint_authority_bump: ctx.bumps.mint_authority,
+        period,
+        limit,
+        period_start: Clock::get()?.unix_timestamp,
+        minted_in_period: 0,
+        hub_address,
+    });
+    Ok(())
+}
+
+pub fn update_token_config(
+    ctx: Context<UpdateTokenConfig>,
+    mint: Pubkey,
+    hub_address: String,
+    enabled: bool,
+    period: u64,
+    limit: u64,
+) -> Result<()> {
+    require!(is_valid_network_address(&hub_address), ContractError::InvalidNetworkAddress);
+    let state = &mut ctx.accounts.state;
+    require!(
+        state.mint_for_hub_address(&hub_address).map_or(true, |registered| registered == mint),
+        ContractError::HubAddressAlreadyRegistered
+    );
+    if let Some(route) = state.tokens.iter_mut().find(|route| route.mint == mint) {
+        route.hub_address = hub_address.clone();
+    }
+    let token_config = &mut ctx.accounts.token_config;
+    token_config.hub_address = hub_address;
+    token_config.enabled = enabled;
+    token_config.period = period;
+    token_config.limit = limit;
+    Ok(())
+}
+
+pub fn create_spoke_mint(
+    ctx: Context<CreateSpokeMint>,
+    decimals: u8,
+    freeze_authority: bool,
+    name: String,
+    symbol: String,
+    uri: String,
+) -> Result<()> {
+    require!(decimals <= HUB_DECIMALS, ContractError::InvalidDecimals);
+    let mint = ctx.accounts.mint.key();
+    let bump = ctx.bumps.mint_authority;
+    let seeds = &[MINT_AUTHORITY_SEED.as_ref(), mint.as_ref(), &[bump]];
+    ctx.accounts.create_metadata(&[&seeds[..]], freeze_authority, name, symbol, uri)
+}
+
+pub fn transfer_mint_authority(
+    ctx: Context<TransferMintAuthority>,
+    mint: Pubkey,
+    new_authority: Pubkey,
+) -> Result<()> {
+    let (mint_authority, _) = ctx.accounts.state.mint_authority(&mint);
+    ctx.accounts.mint_authority_transfer.propose(mint, new_authority, mint_authority)
+}
+
+pub fn cancel_mint_authority_transfer(ctx: Context<CancelMintAuthorityTransfer>, _mint: Pubkey) -> Result<()> {
+    ctx.accounts.mint_authority_transfer.cancel();
+    Ok(())
+}
+
+pub fn accept_mint_authority(ctx: Context<AcceptMintAuthority>) -> Result<()> {
+    let mint = ctx.accounts.mint.key();
+    let bump = [ctx.accounts.state.mint_authority(&mint).1];
+    let mut seeds = ctx.accounts.state.mint_authority_seeds(&mint);
+    seeds.push(&bump);
+    ctx.accounts.hand_over(&[&seeds[..]])
+}
+
+pub fn set_creation_fee_policy(
+    ctx: Context<SetCreationFeePolicy>,
+    charge_on_revert: bool) -> Result<()>{
+    let state: &mut Account<State> = &mut ctx.accounts.state;
+    state.charge_creation_fee_on_revert = charge_on_revert;
+    return  Ok(());
+}
+
+pub fn set_relayer_reimbursement(
+    ctx: Context<SetRelayerReimbursement>,
+    relayer_reimbursement: RelayerReimbursement) -> Result<()>{
+    let state: &mut Account<State> = &mut ctx.accounts.state;
+    state.relayer_reimbursement = relayer_reimbursement;
+    return  Ok(());
+}
+
+balanced_spoke_lib::token_spoke_handlers!(ContractError);
+
 pub fn set_token_creation_fee(
     ctx: Context<SetTokenCreationFee>,
+    mint: Pubkey,
     token_creation_fee: u64,
+    fee_mode: TokenAccountCreationFeeMode,
+    tokens_per_sol: u64,
 ) -> Result<()> {
     let token_creation_account = &mut ctx.accounts.token_account_creation_pda;
+    token_creation_account.token = mint;
     token_creation_account.token_account_creation_fee = token_creation_fee;
+    token_creation_account.fee_mode = fee_mode;
+    token_creation_account.tokens_per_sol = tokens_per_sol;
     return  Ok(());
 }
 
@@ -63,18 +209,18 @@ pub fn cross_transfer<'info>(
     data: Option<Vec<u8>>,
 ) -> Result<u128> {
     require!(icon_hub_value > 0, ContractError::InvalidAmount);
-    let mut value = (icon_hub_value / 10_u128.pow(9)) as u64;
-    if icon_hub_value % 10_u128.pow(9) > 0 {
+    require!(is_valid_network_address(&to), ContractError::InvalidToAddress);
+    require!(ctx.accounts.token_config.enabled, ContractError::TokenDisabled);
+    let factor = hub_amount_factor(ctx.accounts.token_config.decimals);
+    let mut value =
+        u64::try_from(icon_hub_value / factor).map_err(|_| ContractError::InvalidAmount)?;
+    if icon_hub_value % factor > 0 {
         value += 1;
     }
     require!(
         ctx.accounts.from.amount >= value,
         ContractError::InsufficientBalance
     );
-    require!(
-        ctx.accounts.state.spoke_token_addr == ctx.accounts.mint.key(),
-        ContractError::NotSpokeToken
-    );
     let burn_ctx = CpiContext::new(
         ctx.accounts.token_program.to_account_info(),
         Burn {
@@ -93,46 +239,31 @@ fn send_message <'info>(
     value: u128,
     data: Option<Vec<u8>>,
 ) -> Result<u128> {
+    let to_nid = to.split_once('/').map_or("", |(nid, _)| nid).to_string();
     let message: Vec<u8> =
         CrossTransferMsg::create(ctx.accounts.from_authority.key().to_string(), to, value, data.unwrap_or(vec![]))
             .encode();
-    let rollback_message =
-        CrossTransferRevert::create(ctx.accounts.from_authority.key().to_string(), value).encode();
-    let sources = &ctx.accounts.xcall_manager_state.sources;
-    let destinations = &ctx.accounts.xcall_manager_state.destinations;
-    let message = AnyMessage::CallMessageWithRollback(CallMessageWithRollback {
-        data: message,
-        rollback: rollback_message,
-    });
-    let envelope: Envelope = Envelope::new(message, sources.clone(), destinations.clone());
-    let envelope_encoded = rlp::encode(&envelope).to_vec();
-
-    let icon_bn_usd = NetworkAddress::from_str(&ctx.accounts.state.icon_hub_addr)?;
-    let xcall_config = &ctx.remaining_accounts[0];
-    let rollback_account = &ctx.remaining_accounts[1];
-    let sysvar_account = &ctx.remaining_accounts[2];
-    let fee_handler = &ctx.remaining_accounts[3];
-    // the accounts for centralized connections is contained here.
-    let remaining_accounts = ctx.remaining_accounts.split_at(4).1;
-    let xcall_authority = &ctx.accounts.xcall_authority;
-    let cpi_accounts: SendCallCtx = SendCallCtx {
-        config: xcall_config.to_account_info(),
-        rollback_account: Some(rollback_account.to_account_info()),
-        fee_handler: fee_handler.to_account_info(),
-        signer: ctx.accounts.from_authority.to_account_info(),
-        instruction_sysvar: sysvar_account.to_account_info(),
-        dapp_authority: Some(xcall_authority.to_account_info()),
-        system_program: ctx.accounts.system_program.to_account_info(),
-    };
-    let bump = ctx.bumps.xcall_authority;
-    let seeds = &[Authority::SEED_PREFIX.as_ref(), &[bump]];
-    let signer_seeds = &[&seeds[..]];
-    let xcall_program = ctx.accounts.xcall.to_account_info();
-    let cpi_ctx = CpiContext::new_with_signer(xcall_program, cpi_accounts, signer_seeds)
-         .with_remaining_accounts(remaining_accounts.to_vec());
-    let result = xcall::cpi::send_call(cpi_ctx, envelope_encoded, icon_bn_usd)?;
-    Ok(result.get())
-
+    let rollback_message = CrossTransferRevert::create_for_token(
+        ctx.accounts.from_authority.key().to_string(),
+        value,
+        to_nid,
+        ctx.accounts.mint.key().to_string(),
+    )
+    .encode();
+    xcall_cpi::send_message_with_rollback(
+        XcallCpiAccounts {
+            xcall: ctx.accounts.xcall.to_account_info(),
+            signer: ctx.accounts.from_authority.to_account_info(),
+            dapp_authority: ctx.accounts.xcall_authority.to_account_info(),
+            dapp_authority_bump: ctx.bumps.xcall_authority,
+            system_program: ctx.accounts.system_program.to_account_info(),
+            remaining_accounts: ctx.remaining_accounts,
+        },
+        &ctx.accounts.token_config.hub_address,
+        message,
+        rollback_message,
+        &ctx.accounts.xcall_manager_state,
+    )
 }
 
 pub fn handle_call_message<'info>(
@@ -141,29 +272,39 @@ pub fn handle_call_message<'info>(
     data: Vec<u8>,
     protocols: Vec<String>,
 ) -> Result<HandleCallMessageResponse> {
+    // a second execution of a request fails the whole call instead of reporting a failure that the
+    // hub would roll back
+    ctx.accounts.record_message_request()?;
+    call_response(process_call_message(ctx, from, data, protocols), is_permanent_failure)
+}
+
+fn process_call_message<'info>(
+    ctx: Context<'_, '_, '_, 'info, HandleCallMessage<'info>>,
+    from: String,
+    data: Vec<u8>,
+    protocols: Vec<String>,
+) -> Result<()> {
     let state: Account<'info, State> = ctx.accounts.state.clone();
     if !verify_protocols(
-        &ctx.accounts.xcall_manager,
-        &ctx.accounts.xcall_manager_state,
+        ctx.accounts.xcall_manager.to_account_info(),
+        ctx.accounts.xcall_manager_state.to_account_info(),
         &protocols,
     )? {
-        return Ok(HandleCallMessageResponse {
-            success: false,
-            message: ContractError::InvalidProtocols.to_string(),
-        });
+        return Err(ContractError::InvalidProtocols.into());
     }
     let to_authority = ctx.accounts.to_authority.key();
 
-    let bump = ctx.bumps.mint_authority;
-    let seeds = &[b"bnusd_authority".as_ref(), &[bump]];
+    let mint_key = ctx.accounts.mint.key();
+    let bump = [ctx.accounts.token_config.mint_authority_bump];
+    let mut seeds = state.mint_authority_seeds(&mint_key);
+    seeds.push(&bump);
     let signer = &[&seeds[..]];
+    let decimals = ctx.accounts.token_config.decimals;
     let method = decode_method(&data)?;
     if method == CROSS_TRANSFER {
-        if from != state.icon_hub_addr {
-            return Ok(HandleCallMessageResponse {
-                success: false,
-                message: ContractError::InvalidSender.to_string(),
-            });
+        // inbound transfers are routed by the hub token that sent them
+        if from != ctx.accounts.token_config.hub_address {
+            return Err(ContractError::InvalidSender.into());
         }
         let message = decode_cross_transfer(&data)?;
         let recipient_pubkey = Pubkey::from_str(account_from_network_address(message.to)?.as_str())
@@ -171,47 +312,27 @@ pub fn handle_call_message<'info>(
         if recipient_pubkey != to_authority {
             return Err(ContractError::InvalidToAddress.into());
         }
+        // data that is not call data, targets a program outside the allowlist or was skipped with
+        // `skip_call_data` is ignored and the transfer is minted like a plain one
+        let allowlist = ctx.accounts.execution_allowlist.as_ref();
+        let call_skipped = ctx.accounts.message_record.call_skipped;
+        let call = decode_call_data(&message.data).ok().filter(|call| {
+            !call_skipped
+                && allowlist.map_or(false, |allowlist| allowlist.programs.contains(&call.program))
+        });
 
-        let mut mint_amount = translate_incoming_amount(message.value);
-        let recepient_token_balance = ctx.accounts.to.amount;
-
-        if recepient_token_balance == 0 {
-            let token_account_creation_fee = ctx.accounts.token_account_creation_pda.token_account_creation_fee;
-            require!(ctx.accounts.admin_token_account.owner == state.admin, ContractError::InvalidAdmin);
-            require!(
-                mint_amount >= token_account_creation_fee,
-                ContractError::MintAmountLessThanTokenCreationFee
-            );
-            mint(
-                ctx.accounts.mint.to_account_info(),
-                ctx.accounts.admin_token_account.to_account_info(),
-                ctx.accounts.mint_authority.to_account_info(),
-                ctx.accounts.token_program.to_account_info(),
-                token_account_creation_fee,
-                signer,
-            )?;
-
-            mint_amount -= token_account_creation_fee;
+        require!(ctx.accounts.token_config.enabled, ContractError::TokenDisabled);
+        let mint_amount = translate_incoming_amount(message.value, decimals)?;
+        verify_mint_limit(&mut ctx.accounts.token_config, mint_amount)?;
+        ctx.accounts.mint_to_recipient(mint_amount, false, ctx.bumps.fee_pool, signer)?;
+        if let Some(call) = call {
+            execute_call(&call, ctx.remaining_accounts)?;
         }
-        mint(
-            ctx.accounts.mint.to_account_info(),
-            ctx.accounts.to.to_account_info(),
-            ctx.accounts.mint_authority.to_account_info(),
-            ctx.accounts.token_program.to_account_info(),
-            mint_amount,
-            signer,
-        )?;
-        return Ok(HandleCallMessageResponse {
-            success: true,
-            message: "Success".to_owned(),
-        });
+        return Ok(());
     } else if method == CROSS_TRANSFER_REVERT {
         let from_network_address = NetworkAddress::from_str(&from)?;
         if from_network_address.account() != state.xcall.to_string() {
-            return Ok(HandleCallMessageResponse {
-                success: false,
-                message: ContractError::InvalidSender.to_string(),
-            });
+            return Err(ContractError::InvalidSender.into());
         }
         let message = decode_cross_transfer_revert(&data)?;
         let recipient_pubkey =
@@ -219,88 +340,50 @@ pub fn handle_call_message<'info>(
         if recipient_pubkey != to_authority {
             return Err(ContractError::InvalidToAddress.into());
         }
-        mint(
-            ctx.accounts.mint.to_account_info(),
-            ctx.accounts.to.to_account_info(),
-            ctx.accounts.mint_authority.to_account_info(),
-            ctx.accounts.token_program.to_account_info(),
-            translate_incoming_amount(message.amount),
-            signer,
-        )?;
-        return Ok(HandleCallMessageResponse {
-            success: true,
-            message: "Success".to_owned(),
-        });
+        require!(state.revert_mint(&message.token)? == mint_key, ContractError::NotSpokeToken);
+        let mint_amount = translate_incoming_amount(message.amount, decimals)?;
+        ctx.accounts.mint_to_recipient(mint_amount, true, ctx.bumps.fee_pool, signer)?;
+        return Ok(());
     } else {
-        return Ok(HandleCallMessageResponse {
-            success: false,
-            message: ContractError::UnknownMessageType.to_string(),
-        });
+        return Err(ContractError::UnknownMessageType.into());
     }
 }
 
-fn mint<'info>(
-    mint: AccountInfo<'info>,
-    to: AccountInfo<'info>,
-    authority: AccountInfo<'info>,
-    program: AccountInfo<'info>,
-    amount: u64,
-    signer: &[&[&[u8]]],
-) -> Result<()> {
-    let cpi_accounts = MintTo {
-        mint,
-        to,
-        authority,
-    };
-
-    let cpi_ctx = CpiContext::new_with_signer(program, cpi_accounts, signer);
-    token::mint_to(cpi_ctx, amount)?;
-    Ok(())
-}
-
-pub fn verify_protocols<'info>(
-    xcall_manager_program: &Program<'info, XcallManager>,
-    xm_state: &Account<'info, XmState>,
-    protocols: &[String],
-) -> Result<bool> {
-    let cpi_accounts: VerifyProtocols = VerifyProtocols {
-        state: xm_state.to_account_info(),
-    };
-
-    let cpi_ctx = CpiContext::new(xcall_manager_program.to_account_info(), cpi_accounts);
-    let verified = xcall_manager::cpi::verify_protocols(cpi_ctx, protocols.to_vec())?;
-    Ok(verified.get())
-}
-
 pub fn get_handle_call_message_accounts<'info>(
     ctx: Context<'_, '_, '_, 'info, GetParams<'info>>,
+    from: String,
     data: Vec<u8>,
 ) -> Result<ParamAccounts> {
     let method = decode_method(&data)?;
+    let message_record = message_record_address(ctx.program_id, &from, &data);
     if method == CROSS_TRANSFER {
         let message: CrossTransferMsg = decode_cross_transfer(&data)?;
+        let mint = ctx
+            .accounts
+            .state
+            .mint_for_hub_address(&from)
+            .ok_or(ContractError::TokenNotRegistered)?;
 
         let user_address = Pubkey::from_str(account_from_network_address(message.to)?.as_str())
             .map_err(|_| ContractError::NotAnAddress)?;
-        let user_token_address =
-            get_associated_token_address(&user_address, &ctx.accounts.state.spoke_token_addr);
-        let admin_token_address =
-        get_associated_token_address(&ctx.accounts.state.admin, &ctx.accounts.state.spoke_token_addr);
+        let user_token_address = get_associated_token_address(&user_address, &mint);
+        let admin_token_address = get_associated_token_address(&ctx.accounts.state.admin, &mint);
+
+        let call = decode_call_data(&message.data).ok();
 
         Ok(ParamAccounts {
-            accounts: get_accounts(ctx, user_address, user_token_address, admin_token_address)?,
+            accounts: get_accounts(ctx, mint, user_address, user_token_address, admin_token_address, call, message_record)?,
         })
     } else if method == CROSS_TRANSFER_REVERT {
         let message = decode_cross_transfer_revert(&data)?;
+        let mint = ctx.accounts.state.revert_mint(&message.token)?;
         let user_address =
             Pubkey::from_str(&message.account).map_err(|_| ContractError::NotAnAddress)?;
-        let user_token_address =
-            get_associated_token_address(&user_address, &ctx.accounts.state.spoke_token_addr);
-        let admin_token_address =
-        get_associated_token_address(&ctx.accounts.state.admin, &ctx.accounts.state.spoke_token_addr);
+        let user_token_address = get_associated_token_address(&user_address, &mint);
+        let admin_token_address = get_associated_token_address(&ctx.accounts.state.admin, &mint);
 
         Ok(ParamAccounts {
-            accounts: get_accounts(ctx, user_address, user_token_address, admin_token_address)?,
+            accounts: get_accounts(ctx, mint, user_address, user_token_address, admin_token_address, None, message_record)?,
         })
     } else {
         let accounts: Vec<ParamAccountProps> = vec![];
@@ -308,50 +391,128 @@ pub fn get_handle_call_message_accounts<'info>(
     }
 }
 
+pub fn migrate<'info>(ctx: Context<'_, '_, '_, 'info, Migrate<'info>>) -> Result<()> {
+    let admin = ctx.accounts.admin.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+    let state_info = ctx.accounts.state.to_account_info();
+    require!(state_info.data_len() < 8 + State::INIT_SPACE, ContractError::AlreadyMigrated);
+    let legacy = {
+        let data = state_info.try_borrow_data()?;
+        require!(data.len() >= 8 && data[..8] == State::DISCRIMINATOR, ContractError::AlreadyMigrated);
+        LegacyState::deserialize(&mut &data[8..])?
+    };
+    require_keys_eq!(legacy.admin, admin.key(), ContractError::OnlyAdmin);
+    let mint = ctx.accounts.mint.key();
+    require_keys_eq!(legacy.spoke_token_addr, mint, ContractError::NotSpokeToken);
+
+    grow_account(&state_info, &admin, &system_program, 8 + State::INIT_SPACE)?;
+    let state = State {
+        xcall: legacy.xcall,
+        admin: legacy.admin,
+        xcall_manager: legacy.xcall_manager,
+        xcall_manager_state: legacy.xcall_manager_state,
+        charge_creation_fee_on_revert: false,
+        relayer_reimbursement: RelayerReimbursement::Admin,
+        legacy_mint: mint,
+        tokens: vec![TokenRoute {
+            mint,
+            hub_address: legacy.icon_hub_addr.clone(),
+        }],
+    };
+    state.try_serialize(&mut &mut state_info.try_borrow_mut_data()?[..])?;
+
+    ctx.accounts.token_config.set_inner(TokenConfig {
+        mint,
+        decimals: ctx.accounts.mint.decimals,
+        enabled: true,
+        mint_authority_bump: ctx.bumps.legacy_mint_authority,
+        period: 0,
+        limit: 0,
+        period_start: Clock::get()?.unix_timestamp,
+        minted_in_period: 0,
+        hub_address: legacy.icon_hub_addr,
+    });
+
+    // the single mint version kept a fixed fee in an account without the mint in its seeds
+    let legacy_fee_info = ctx.accounts.legacy_token_account_creation_pda.to_account_info();
+    let mut token_account_creation_fee = 0;
+    if legacy_fee_info.owner == ctx.program_id {
+        {
+            let data = legacy_fee_info.try_borrow_data()?;
+            if data.len() >= 16 {
+                token_account_creation_fee = u64::from_le_bytes(data[8..16].try_into().unwrap());
+            }
+        }
+        let lamports = legacy_fee_info.lamports();
+        **legacy_fee_info.try_borrow_mut_lamports()? = 0;
+        **admin.try_borrow_mut_lamports()? += lamports;
+        legacy_fee_info.assign(&system_program::ID);
+        legacy_fee_info.realloc(0, false)?;
+    }
+    ctx.accounts.token_account_creation_pda.set_inner(TokenAccountCreationFee {
+        token: mint,
+        token_account_creation_fee,
+        fee_mode: TokenAccountCreationFeeMode::Fixed,
+        tokens_per_sol: 0,
+    });
+    Ok(())
+}
+
+pub fn close_message_record<'info>(
+    ctx: Context<'_, '_, '_, 'info, CloseMessageRecord<'info>>,
+) -> Result<()> {
+    message_record::require_requests_closed(
+        &ctx.accounts.message_record.requests,
+        ctx.remaining_accounts,
+    )
+}
+
 pub fn force_rollback<'info>(
     ctx: Context<'_, '_, '_, 'info, ForceRollback<'info>>,
     request_id: u128,
     source_nid: String,
     connection_sn: u128,
     dst_program_id: Pubkey,
-
 )->Result<()> {
-    let bump = ctx.bumps.xcall_authority;
-    let seeds = &[Authority::SEED_PREFIX.as_ref(), &[bump]];
-    let signer_seeds = &[&seeds[..]];
-
-    let proxy_request = &ctx.remaining_accounts[0];
-    let config = &ctx.remaining_accounts[1];
-    let admin = &ctx.remaining_accounts[2];
-
-    let remaining_accounts: &[AccountInfo<'info>] = ctx.remaining_accounts.split_at(3).1;
-    let cpi_accounts: HandleForcedRollbackCtx =  HandleForcedRollbackCtx{
-        proxy_request: proxy_request.to_account_info(),
-        signer: ctx.accounts.signer.to_account_info(),
-        dapp_authority: ctx.accounts.xcall_authority.to_account_info(),
-        config: config.to_account_info(),
-        system_program: ctx.accounts.system_program.to_account_info(),
-        admin: admin.to_account_info()
-    };
+    ctx.accounts.force_rollback(
+        ctx.bumps.xcall_authority,
+        ctx.remaining_accounts,
+        request_id,
+        source_nid,
+        connection_sn,
+        dst_program_id,
+    )
+}
 
-    let xcall_program = ctx.accounts.xcall.to_account_info();
-    let cpi_ctx = CpiContext::new_with_signer(xcall_program, cpi_accounts, signer_seeds)
-    .with_remaining_accounts(remaining_accounts.to_vec());
-        
-    let _result = xcall::cpi::handle_forced_rollback(cpi_ctx, request_id, source_nid, connection_sn, dst_program_id)?;
-    Ok(())
+// hub tokens use 18 decimals, amounts are scaled down to the decimals of the spoke mint
+fn hub_amount_factor(decimals: u8) -> u128 {
+    10_u128.pow((HUB_DECIMALS - decimals) as u32)
 }
 
-pub fn account_from_network_address(string_network_address: String) -> Result<String> {
-    let parts = string_network_address.split('/').collect::<Vec<&str>>();
-    require!(parts.len() == 2, ContractError::InvalidNetworkAddress);
-    Ok(parts[1].to_string())
+pub fn translate_outgoing_amount(amount: u64, decimals: u8) -> u128 {
+    (amount as u128) * hub_amount_factor(decimals)
 }
 
-pub fn translate_outgoing_amount(amount: u64) -> u128 {
-    (amount as u128) * 10_u64.pow(9) as u128
+pub fn translate_incoming_amount(amount: u128, decimals: u8) -> Result<u64> {
+    u64::try_from(amount / hub_amount_factor(decimals))
+        .map_err(|_| ContractError::InvalidAmount.into())
 }
 
-pub fn translate_incoming_amount(amount: u128) -> u64 {
-    (amount / 10_u64.pow(9) as u128) as u64
+// resets the window once `period` has passed, a zero period leaves minting unlimited
+fn verify_mint_limit(token_config: &mut TokenConfig, amount: u64) -> Result<()> {
+    if token_config.period == 0 {
+        return Ok(());
+    }
+    let now = Clock::get()?.unix_timestamp;
+    if now.saturating_sub(token_config.period_start) >= token_config.period as i64 {
+        token_config.period_start = now;
+        token_config.minted_in_period = 0;
+    }
+    let minted = token_config
+        .minted_in_period
+        .checked_add(amount)
+        .ok_or(ContractError::MintLimitExceeded)?;
+    require!(minted <= token_config.limit, ContractError::MintLimitExceeded);
+    token_config.minted_in_period = minted;
+    Ok(())
 }
@@ -1,10 +1,8 @@
 use anchor_lang::prelude::*;
 pub mod errors;
-pub mod helpers;
 pub mod instructions;
 pub mod param_accounts;
 pub mod states;
-pub mod structs;
 use states::*;
 use xcall_lib::xcall_dapp_type::HandleCallMessageResponse;
 
@@ -17,17 +15,13 @@ pub mod spoke_token {
     pub fn initialize(
         ctx: Context<Initialize>,
         xcall: Pubkey,
-        icon_hub_addr: String,
         xcall_manager: Pubkey,
-        spoke_token_addr: Pubkey,
         xcall_manager_state: Pubkey,
     ) -> Result<()> {
         instructions::initialize(
             ctx,
             xcall,
-            icon_hub_addr,
             xcall_manager,
-            spoke_token_addr,
             xcall_manager_state,
         )
     }
@@ -42,13 +36,118 @@ pub mod spoke_token {
         )
     }
 
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        xcall: Option<Pubkey>,
+        xcall_manager: Option<Pubkey>,
+        xcall_manager_state: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::update_config(ctx, xcall, xcall_manager, xcall_manager_state)
+    }
+
+    pub fn register_token(
+        ctx: Context<RegisterToken>,
+        hub_address: String,
+        period: u64,
+        limit: u64,
+    ) -> Result<()> {
+        instructions::register_token(ctx, hub_address, period, limit)
+    }
+
+    pub fn update_token_config(
+        ctx: Context<UpdateTokenConfig>,
+        mint: Pubkey,
+        hub_address: String,
+        enabled: bool,
+        period: u64,
+        limit: u64,
+    ) -> Result<()> {
+        instructions::update_token_config(ctx, mint, hub_address, enabled, period, limit)
+    }
+
+    pub fn create_spoke_mint(
+        ctx: Context<CreateSpokeMint>,
+        decimals: u8,
+        freeze_authority: bool,
+        name: String,
+        symbol: String,
+        uri: String,
+    ) -> Result<()> {
+        instructions::create_spoke_mint(ctx, decimals, freeze_authority, name, symbol, uri)
+    }
+
+    pub fn transfer_mint_authority(
+        ctx: Context<TransferMintAuthority>,
+        mint: Pubkey,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        instructions::transfer_mint_authority(ctx, mint, new_authority)
+    }
+
+    pub fn cancel_mint_authority_transfer(
+        ctx: Context<CancelMintAuthorityTransfer>,
+        mint: Pubkey,
+    ) -> Result<()> {
+        instructions::cancel_mint_authority_transfer(ctx, mint)
+    }
+
+    pub fn accept_mint_authority(ctx: Context<AcceptMintAuthority>) -> Result<()> {
+        instructions::accept_mint_authority(ctx)
+    }
+
+    pub fn set_creation_fee_policy(
+        ctx: Context<SetCreationFeePolicy>,
+        charge_on_revert: bool,
+    ) -> Result<()> {
+        instructions::set_creation_fee_policy(ctx, charge_on_revert)
+    }
+
+    pub fn set_relayer_reimbursement(
+        ctx: Context<SetRelayerReimbursement>,
+        relayer_reimbursement: RelayerReimbursement,
+    ) -> Result<()> {
+        instructions::set_relayer_reimbursement(ctx, relayer_reimbursement)
+    }
+
+    pub fn fund_fee_pool(ctx: Context<ManageFeePool>, amount: u64) -> Result<()> {
+        instructions::fund_fee_pool(ctx, amount)
+    }
+
+    pub fn drain_fee_pool(ctx: Context<ManageFeePool>, amount: u64) -> Result<()> {
+        instructions::drain_fee_pool(ctx, amount)
+    }
+
+    pub fn add_allowed_program(
+        ctx: Context<ConfigureExecutionAllowlist>,
+        program: Pubkey,
+    ) -> Result<()> {
+        instructions::add_allowed_program(ctx, program)
+    }
+
+    pub fn remove_allowed_program(
+        ctx: Context<ConfigureExecutionAllowlist>,
+        program: Pubkey,
+    ) -> Result<()> {
+        instructions::remove_allowed_program(ctx, program)
+    }
+
+    pub fn skip_call_data(ctx: Context<SkipCallData>, from: String, data: Vec<u8>) -> Result<()> {
+        instructions::skip_call_data(ctx, from, data)
+    }
+
     pub fn set_token_creation_fee(
         ctx: Context<SetTokenCreationFee>,
+        mint: Pubkey,
         token_creation_fee: u64,
+        fee_mode: TokenAccountCreationFeeMode,
+        tokens_per_sol: u64,
     ) -> Result<()> {
         instructions::set_token_creation_fee(
             ctx,
-            token_creation_fee
+            mint,
+            token_creation_fee,
+            fee_mode,
+            tokens_per_sol
         )
     }
 
@@ -70,6 +169,12 @@ pub mod spoke_token {
         instructions::handle_call_message(ctx, from, data, protocols)
     }
 
+    pub fn close_message_record<'info>(
+        ctx: Context<'_, '_, '_, 'info, CloseMessageRecord<'info>>,
+    ) -> Result<()> {
+        instructions::close_message_record(ctx)
+    }
+
     pub fn force_rollback<'info>(
         ctx: Context<'_, '_, '_, 'info, ForceRollback<'info>>,
         request_id: u128,
@@ -80,12 +185,16 @@ pub mod spoke_token {
         instructions::force_rollback(ctx, request_id, source_nid, connection_sn, dst_program_id)
     }
 
+    pub fn migrate<'info>(ctx: Context<'_, '_, '_, 'info, Migrate<'info>>) -> Result<()> {
+        instructions::migrate(ctx)
+    }
+
     pub fn query_handle_call_message_accounts<'info>(
         ctx: Context<'_, '_, '_, 'info, GetParams<'info>>,
-        _from: String,
+        from: String,
         data: Vec<u8>,
         _protocols: Vec<String>,
     ) -> Result<ParamAccounts> {
-        return instructions::get_handle_call_message_accounts(ctx, data);
+        return instructions::get_handle_call_message_accounts(ctx, from, data);
     }
 }
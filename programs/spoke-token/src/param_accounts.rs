@@ -1,21 +1,28 @@
 use crate::{id, states::*};
-use anchor_lang::{prelude::*, solana_program::system_program};
+use anchor_lang::{prelude::*, solana_program::{system_program, sysvar}};
 use anchor_spl::{associated_token, token::ID as TOKEN_PROGRAM_ID};
+use balanced_spoke_lib::call_data::CallData;
 
 pub fn get_accounts<'info>(
     ctx: Context<'_, '_, '_, 'info, GetParams<'info>>,
+    mint: Pubkey,
     to_authority: Pubkey,
     to: Pubkey,
     admin_token_account: Pubkey,
+    call: Option<CallData>,
+    message_record: Pubkey,
 ) -> Result<Vec<ParamAccountProps>> {
-    let (token_account_creation_pda,_) = Pubkey::find_program_address(&[TOKEN_CREATION_ACCOUNT_SEED], &id());
+    let (token_account_creation_pda,_) = Pubkey::find_program_address(&[TOKEN_CREATION_ACCOUNT_SEED, mint.as_ref()], &id());
+    let (token_config,_) = Pubkey::find_program_address(&[TOKEN_CONFIG_SEED, mint.as_ref()], &id());
+    let (fee_pool,_) = Pubkey::find_program_address(&[FEE_POOL_SEED], &id());
 
-    let accounts: Vec<ParamAccountProps> = vec![
+    let mut accounts: Vec<ParamAccountProps> = vec![
         ParamAccountProps::new(ctx.accounts.state.key(), false),
         ParamAccountProps::new(to, false),
         ParamAccountProps::new_readonly(to_authority, false),
-        ParamAccountProps::new(ctx.accounts.state.spoke_token_addr, false),
-        ParamAccountProps::new(mint_authority(&ctx.program_id)?.0, false),
+        ParamAccountProps::new(mint, false),
+        ParamAccountProps::new(token_config, false),
+        ParamAccountProps::new(ctx.accounts.state.mint_authority(&mint).0, false),
         ParamAccountProps::new(TOKEN_PROGRAM_ID, false),
         ParamAccountProps::new_readonly(associated_token::ID, false),
         ParamAccountProps::new_readonly(ctx.accounts.state.xcall_manager, false),
@@ -24,14 +31,24 @@ pub fn get_accounts<'info>(
         ParamAccountProps::new_readonly(system_program::id(), false),
         ParamAccountProps::new(admin_token_account, false),
         ParamAccountProps::new(token_account_creation_pda, false),
+        ParamAccountProps::new(fee_pool, false),
     ];
+    let execution_allowlist = match call {
+        Some(_) => Pubkey::find_program_address(&[EXECUTION_ALLOWLIST_SEED], &id()).0,
+        None => id(),
+    };
+    accounts.push(ParamAccountProps::new_readonly(execution_allowlist, false));
+    accounts.push(ParamAccountProps::new(message_record, false));
+    accounts.push(ParamAccountProps::new_readonly(sysvar::instructions::ID, false));
+    if let Some(call) = call {
+        for account in call.accounts {
+            accounts.push(if account.is_writable {
+                ParamAccountProps::new(account.pubkey, false)
+            } else {
+                ParamAccountProps::new_readonly(account.pubkey, false)
+            });
+        }
+        accounts.push(ParamAccountProps::new_readonly(call.program, false));
+    }
     Ok(accounts)
 }
-
-pub fn mint_authority<'info>(
-    program_id: &Pubkey
-) -> Result<(Pubkey, u8)> {
-    let seeds: &[&[u8]] = &[b"bnusd_authority"];
-    let (pda, bump) = Pubkey::find_program_address(seeds, program_id);
-    Ok((pda, bump))
-}
@@ -1,15 +1,31 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, solana_program::program_option::COption};
 use anchor_spl::{
-    associated_token,
+    associated_token::{self, get_associated_token_address},
+    metadata::Metadata,
     token::{Mint, Token, TokenAccount},
 };
-use xcall::program::Xcall;
+use std::str::FromStr;
 use xcall_manager::{self, program::XcallManager};
 
-use crate::errors::ContractError;
+pub use balanced_spoke_lib::{
+    creation_fee::{RelayerReimbursement, TokenAccountCreationFeeMode, FEE_POOL_SEED},
+    params::{ParamAccountProps, ParamAccounts},
+    token_spoke::{EXECUTION_ALLOWLIST_SEED, MINT_AUTHORITY_TRANSFER_SEED},
+};
+use balanced_spoke_lib::{
+    errors::SpokeError,
+    message_record::{message_hash, MESSAGE_RECORD_SEED},
+    xcall_pda::{config_address, proxy_request_address},
+};
+
+use crate::{errors::ContractError, program::SpokeToken};
 pub const STATE_SEED: &'static [u8; 5] = b"state";
-pub const AUTHORITY_SEED: &'static [u8; 15] = b"bnusd_authority";
+pub const MINT_AUTHORITY_SEED: &'static [u8; 14] = b"mint_authority";
+// mint authority of the mint the program was deployed with, from before it handled more than one mint
+pub const LEGACY_MINT_AUTHORITY_SEED: &'static [u8; 15] = b"bnusd_authority";
+pub const TOKEN_CONFIG_SEED: &'static [u8; 12] = b"token_config";
 pub const TOKEN_CREATION_ACCOUNT_SEED: &'static [u8; 14] = b"token_creation";
+pub const MAX_SPOKE_TOKENS: usize = 20;
 
 #[derive(Accounts)]
 pub struct Initialize<'info> {
@@ -17,6 +33,10 @@ pub struct Initialize<'info> {
     pub state: Account<'info, State>,
     #[account(mut)]
     pub admin: Signer<'info>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, SpokeToken>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ContractError::OnlyUpgradeAuthority)]
+    pub program_data: Account<'info, ProgramData>,
     pub system_program: Program<'info, System>,
 }
 
@@ -29,8 +49,112 @@ pub struct SetAdmin<'info> {
 }
 
 #[derive(Accounts)]
+#[instruction(decimals: u8)]
+pub struct CreateSpokeMint<'info> {
+    #[account(mut, address=state.admin @ContractError::OnlyAdmin)]
+    pub admin: Signer<'info>,
+    #[account(mut, seeds=[STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    #[account(
+        init,
+        payer = admin,
+        mint::decimals = decimals,
+        mint::authority = mint_authority,
+        mint::freeze_authority = mint_authority
+    )]
+    pub mint: Account<'info, Mint>,
+    /// CHECK: program pda that holds the mint and metadata update authority
+    #[account(seeds=[MINT_AUTHORITY_SEED, mint.key().as_ref()], bump)]
+    pub mint_authority: AccountInfo<'info>,
+    /// CHECK: metadata pda, created and validated by the token metadata program
+    #[account(
+        mut,
+        seeds=[b"metadata", token_metadata_program.key().as_ref(), mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub metadata: AccountInfo<'info>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(mint: Pubkey)]
+pub struct TransferMintAuthority<'info> {
+    #[account(init_if_needed, payer = admin, seeds=[MINT_AUTHORITY_TRANSFER_SEED, mint.as_ref()], bump, space = 8 + MintAuthorityTransfer::INIT_SPACE)]
+    pub mint_authority_transfer: Account<'info, MintAuthorityTransfer>,
+    #[account(seeds=[STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    #[account(mut, address=state.admin @ContractError::OnlyAdmin)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(mint: Pubkey)]
+pub struct CancelMintAuthorityTransfer<'info> {
+    #[account(mut, close = admin, seeds=[MINT_AUTHORITY_TRANSFER_SEED, mint.as_ref()], bump)]
+    pub mint_authority_transfer: Account<'info, MintAuthorityTransfer>,
+    #[account(seeds=[STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    #[account(mut, address=state.admin @ContractError::OnlyAdmin)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptMintAuthority<'info> {
+    #[account(
+        mut,
+        close = admin,
+        seeds=[MINT_AUTHORITY_TRANSFER_SEED, mint.key().as_ref()],
+        bump,
+        has_one = new_authority @ContractError::InvalidMintAuthority
+    )]
+    pub mint_authority_transfer: Account<'info, MintAuthorityTransfer>,
+    #[account(seeds=[STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    /// CHECK: receives the rent of the closed transfer account
+    #[account(mut, address=state.admin @ContractError::OnlyAdmin)]
+    pub admin: AccountInfo<'info>,
+    pub new_authority: Signer<'info>,
+    #[account(mut)]
+    pub mint: Account<'info, Mint>,
+    /// CHECK: program pda that currently holds the mint authority
+    #[account(address=state.mint_authority(&mint.key()).0 @ContractError::InvalidMintAuthority)]
+    pub mint_authority: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(mut, seeds=[STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    #[account(address=state.admin @ContractError::OnlyAdmin)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetCreationFeePolicy<'info> {
+    #[account(mut, seeds=[STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    #[account(address=state.admin @ContractError::OnlyAdmin)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetRelayerReimbursement<'info> {
+    #[account(mut, seeds=[STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    #[account(address=state.admin @ContractError::OnlyAdmin)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(mint: Pubkey)]
 pub struct SetTokenCreationFee<'info> {
-    #[account(init, payer = admin, seeds=[TOKEN_CREATION_ACCOUNT_SEED], bump, space = 8 + TokenAccountCreationFee::INIT_SPACE)]
+    #[account(init_if_needed, payer = admin, seeds=[TOKEN_CREATION_ACCOUNT_SEED, mint.as_ref()], bump, space = 8 + TokenAccountCreationFee::INIT_SPACE)]
     pub token_account_creation_pda: Account<'info, TokenAccountCreationFee>,
     #[account(mut, seeds=[STATE_SEED], bump)]
     pub state: Account<'info, State>,
@@ -39,6 +163,39 @@ pub struct SetTokenCreationFee<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct RegisterToken<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(mut, has_one=admin, seeds=[STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+
+    #[account(constraint = mint.mint_authority == COption::Some(mint_authority.key()) @ContractError::InvalidMintAuthority)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(init, payer=admin, space = 8 + TokenConfig::INIT_SPACE, seeds=[TOKEN_CONFIG_SEED, mint.key().as_ref()], bump)]
+    pub token_config: Account<'info, TokenConfig>,
+
+    /// CHECK: mint authority pda of this mint, the mint has to be handed to it before registering
+    #[account(seeds=[MINT_AUTHORITY_SEED, mint.key().as_ref()], bump)]
+    pub mint_authority: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(mint: Pubkey)]
+pub struct UpdateTokenConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(mut, has_one=admin, seeds=[STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+
+    #[account(mut, seeds=[TOKEN_CONFIG_SEED, mint.as_ref()], bump)]
+    pub token_config: Account<'info, TokenConfig>,
+}
+
 #[derive(Accounts)]
 pub struct CrossTransfer<'info> {
     #[account(mut)]
@@ -48,25 +205,36 @@ pub struct CrossTransfer<'info> {
 
     #[account(mut, seeds=[STATE_SEED], bump)]
     pub state: Account<'info, State>,
-    #[account(mut, constraint=mint.key()==state.spoke_token_addr)]
+    #[account(mut)]
     pub mint: Account<'info, Mint>,
+    #[account(seeds=[TOKEN_CONFIG_SEED, mint.key().as_ref()], bump)]
+    pub token_config: Account<'info, TokenConfig>,
 
     #[account(constraint=xcall_manager_state.key() ==state.xcall_manager_state @ContractError::InvalidXcallManagerState)]
     pub xcall_manager_state: Account<'info, xcall_manager::XmState>,
-    //xcall validates this account
-    //not additionally used in balanced
-    #[account(mut)]
-    pub xcall_config: Account<'info, xcall::state::Config>,
+    /// CHECK: config of the xcall set in the state
+    #[account(
+        mut,
+        address = config_address(&state.xcall) @SpokeError::InvalidXcallConfig,
+        owner = state.xcall @SpokeError::InvalidXcallConfig
+    )]
+    pub xcall_config: UncheckedAccount<'info>,
     #[account(
         init_if_needed, payer=from_authority, space = Authority::MAX_SPACE, seeds = [Authority::SEED_PREFIX], bump
       )]
     pub xcall_authority: Account<'info, Authority>,
-    pub xcall: Program<'info, Xcall>,
+    /// CHECK: the xcall set in the state, which may differ from the one the program was built against
+    #[account(
+        address = state.xcall @SpokeError::InvalidXcallProgram,
+        constraint = xcall.executable @SpokeError::InvalidXcallProgram
+    )]
+    pub xcall: UncheckedAccount<'info>,
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
+#[instruction(from: String, data: Vec<u8>)]
 pub struct HandleCallMessage<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
@@ -74,35 +242,59 @@ pub struct HandleCallMessage<'info> {
     pub xcall_singer: Signer<'info>,
     #[account(mut, seeds=[STATE_SEED], bump)]
     pub state: Account<'info, State>,
+    /// CHECK: associated token account of `to_authority`, created by the handler when missing
     #[account(
-        init_if_needed,
-        payer = signer,
-        associated_token::mint = mint,
-        associated_token::authority = to_authority
+        mut,
+        address = get_associated_token_address(&to_authority.key(), &mint.key()) @ContractError::InvalidToAddress
     )]
-    pub to: Account<'info, TokenAccount>,
+    pub to: UncheckedAccount<'info>,
     /// CHECK: this account is validated inside instruction logic
     pub to_authority: AccountInfo<'info>,
 
-    #[account(mut, constraint=mint.key()==state.spoke_token_addr)]
+    #[account(mut)]
     pub mint: Account<'info, Mint>,
+    #[account(mut, seeds=[TOKEN_CONFIG_SEED, mint.key().as_ref()], bump)]
+    pub token_config: Account<'info, TokenConfig>,
     ///CHECK: program signs onbehalf of the authority pda
     /// no additional validation is required as mint is already validated separately 
-    #[account(seeds = [AUTHORITY_SEED], bump)]
+    #[account(
+        address = state.mint_authority_address(&mint.key(), token_config.mint_authority_bump)?
+            @ContractError::InvalidMintAuthority
+    )]
     pub mint_authority: AccountInfo<'info>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
     pub xcall_manager: Program<'info, XcallManager>,
-    pub xcall: Program<'info, Xcall>,
+    /// CHECK: the xcall set in the state, which may differ from the one the program was built against
+    #[account(
+        address = state.xcall @SpokeError::InvalidXcallProgram,
+        constraint = xcall.executable @SpokeError::InvalidXcallProgram
+    )]
+    pub xcall: UncheckedAccount<'info>,
 
     #[account(constraint=xcall_manager_state.key()==state.xcall_manager_state @ContractError::InvalidXcallManagerState)]
     pub xcall_manager_state: Account<'info, xcall_manager::XmState>,
     pub system_program: Program<'info, System>,
 
     pub admin_token_account: Account<'info, TokenAccount>,
-    #[account(mut, seeds=[TOKEN_CREATION_ACCOUNT_SEED], bump)]
+    #[account(mut, seeds=[TOKEN_CREATION_ACCOUNT_SEED, mint.key().as_ref()], bump)]
     pub token_account_creation_pda: Account<'info, TokenAccountCreationFee>,
-
+    /// CHECK: fee pool pda, only required when relayers are reimbursed in lamports
+    #[account(mut, seeds=[FEE_POOL_SEED], bump)]
+    pub fee_pool: Option<AccountInfo<'info>>,
+    #[account(seeds=[EXECUTION_ALLOWLIST_SEED], bump)]
+    pub execution_allowlist: Option<Account<'info, ExecutionAllowlist>>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = 8 + MessageRecord::INIT_SPACE,
+        seeds = [MESSAGE_RECORD_SEED, &message_hash(&from, &data)],
+        bump
+    )]
+    pub message_record: Account<'info, MessageRecord>,
+    /// CHECK: instructions sysvar, read for the xcall request being executed
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions_sysvar: AccountInfo<'info>,
 }
 
 #[account]
@@ -110,19 +302,101 @@ pub struct HandleCallMessage<'info> {
 pub struct State {
     pub xcall: Pubkey,
     pub admin: Pubkey,
+    pub xcall_manager: Pubkey,
+    pub xcall_manager_state: Pubkey,
+    pub charge_creation_fee_on_revert: bool,
+    pub relayer_reimbursement: RelayerReimbursement,
+    // mint the program was deployed with when it handled a single mint, default otherwise
+    pub legacy_mint: Pubkey,
+    // hub token address to mint index, xcall only hands the state account to
+    // `query_handle_call_message_accounts` so routing has to be readable from here
+    #[max_len(20)]
+    pub tokens: Vec<TokenRoute>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, InitSpace)]
+pub struct TokenRoute {
+    pub mint: Pubkey,
     #[max_len(100)]
+    pub hub_address: String,
+}
+
+impl State {
+    pub fn mint_for_hub_address(&self, hub_address: &str) -> Option<Pubkey> {
+        self.tokens
+            .iter()
+            .find(|route| route.hub_address == hub_address)
+            .map(|route| route.mint)
+    }
+
+    // the legacy mint keeps the single authority pda it was created with
+    pub fn mint_authority_seeds<'a>(&self, mint: &'a Pubkey) -> Vec<&'a [u8]> {
+        if *mint == self.legacy_mint {
+            vec![LEGACY_MINT_AUTHORITY_SEED.as_ref()]
+        } else {
+            vec![MINT_AUTHORITY_SEED.as_ref(), mint.as_ref()]
+        }
+    }
+
+    pub fn mint_authority(&self, mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&self.mint_authority_seeds(mint), &crate::ID)
+    }
+
+    pub fn mint_authority_address(&self, mint: &Pubkey, bump: u8) -> Result<Pubkey> {
+        let bump = [bump];
+        let mut seeds = self.mint_authority_seeds(mint);
+        seeds.push(&bump);
+        Pubkey::create_program_address(&seeds, &crate::ID)
+            .map_err(|_| ContractError::InvalidMintAuthority.into())
+    }
+
+    // reverts recorded before the program handled more than one mint carry no token, they are
+    // for the legacy mint
+    pub fn revert_mint(&self, token: &str) -> Result<Pubkey> {
+        if token.is_empty() {
+            require!(self.legacy_mint != Pubkey::default(), ContractError::NotAnAddress);
+            return Ok(self.legacy_mint);
+        }
+        Pubkey::from_str(token).map_err(|_| ContractError::NotAnAddress.into())
+    }
+}
+
+// state layout of the single mint version, read once by `migrate`
+#[derive(AnchorDeserialize)]
+pub struct LegacyState {
+    pub xcall: Pubkey,
+    pub admin: Pubkey,
     pub icon_hub_addr: String,
     pub xcall_manager: Pubkey,
     pub spoke_token_addr: Pubkey,
     pub xcall_manager_state: Pubkey,
 }
 
+// fixed size fields come first so clients can filter registered tokens with getProgramAccounts
 #[account]
 #[derive(InitSpace)]
-pub struct TokenAccountCreationFee {
-    pub token_account_creation_fee: u64
+pub struct TokenConfig {
+    pub mint: Pubkey,
+    pub decimals: u8,
+    pub enabled: bool,
+    pub mint_authority_bump: u8,
+    // inbound transfers can mint at most `limit` per `period` seconds, a zero period disables it
+    pub period: u64,
+    pub limit: u64,
+    pub period_start: i64,
+    pub minted_in_period: u64,
+    #[max_len(100)]
+    pub hub_address: String,
 }
 
+#[event]
+pub struct ConfigUpdated {
+    pub xcall: Pubkey,
+    pub xcall_manager: Pubkey,
+    pub xcall_manager_state: Pubkey,
+}
+
+balanced_spoke_lib::token_account_creation_fee!(token: Pubkey);
 
 #[derive(Accounts)]
 pub struct GetParams<'info> {
@@ -130,57 +404,41 @@ pub struct GetParams<'info> {
     pub state: Account<'info, State>,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Debug)]
-pub struct ParamAccountProps {
-    pub pubkey: Pubkey,
-    pub is_writable: bool,
-    pub is_signer: bool,
-}
-
-#[derive(AnchorSerialize, AnchorDeserialize, Debug)]
-pub struct ParamAccounts {
-    pub accounts: Vec<ParamAccountProps>,
-}
-
+// moves a deployment of the single mint version to the current layout, the mint it was deployed
+// with is registered with its original authority and its creation fee moves to the per mint account
 #[derive(Accounts)]
-pub struct ForceRollback<'info> {
-    #[account(seeds = [STATE_SEED], bump)]
-    pub state: Account<'info, State>,
-    #[account(mut, address=state.admin @ContractError::OnlyAdmin)]
-    pub signer: Signer<'info>,
-    pub xcall: Program<'info, Xcall>,
+pub struct Migrate<'info> {
+    /// CHECK: legacy layout, rewritten by the handler which also checks the admin
+    #[account(mut, seeds = [STATE_SEED], bump)]
+    pub state: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(constraint = mint.mint_authority == COption::Some(legacy_mint_authority.key()) @ContractError::InvalidMintAuthority)]
+    pub mint: Account<'info, Mint>,
+    #[account(init, payer = admin, space = 8 + TokenConfig::INIT_SPACE, seeds = [TOKEN_CONFIG_SEED, mint.key().as_ref()], bump)]
+    pub token_config: Account<'info, TokenConfig>,
+    /// CHECK: mint authority pda of the single mint version
+    #[account(seeds = [LEGACY_MINT_AUTHORITY_SEED], bump)]
+    pub legacy_mint_authority: UncheckedAccount<'info>,
+    /// CHECK: creation fee account of the single mint version, closed once its fee is moved
+    #[account(mut, seeds = [TOKEN_CREATION_ACCOUNT_SEED], bump)]
+    pub legacy_token_account_creation_pda: UncheckedAccount<'info>,
     #[account(
-      init_if_needed, payer=signer, space = Authority::MAX_SPACE, seeds = [Authority::SEED_PREFIX], bump
+        init_if_needed,
+        payer = admin,
+        space = 8 + TokenAccountCreationFee::INIT_SPACE,
+        seeds = [TOKEN_CREATION_ACCOUNT_SEED, mint.key().as_ref()],
+        bump
     )]
-    pub xcall_authority: Account<'info, Authority>,
+    pub token_account_creation_pda: Account<'info, TokenAccountCreationFee>,
     pub system_program: Program<'info, System>,
-    
 }
 
-impl ParamAccountProps {
-    pub fn new(pubkey: Pubkey, is_signer: bool) -> Self {
-        Self {
-            pubkey,
-            is_signer,
-            is_writable: true,
-        }
-    }
+balanced_spoke_lib::token_spoke_accounts!(ContractError);
 
-    pub fn new_readonly(pubkey: Pubkey, is_signer: bool) -> Self {
-        Self {
-            pubkey,
-            is_signer,
-            is_writable: false,
-        }
-    }
-}
+balanced_spoke_lib::force_rollback!(ContractError::OnlyAdmin);
 
-#[account]
-pub struct Authority {
-    pub bump: u8,
-}
+balanced_spoke_lib::message_record!();
+balanced_spoke_lib::close_message_record!(ContractError::OnlyAdmin);
 
-impl Authority {
-    pub const SEED_PREFIX: &'static [u8; 14] = b"dapp_authority";
-    pub const MAX_SPACE: usize = 8 + 1;
-}
+balanced_spoke_lib::dapp_authority!();
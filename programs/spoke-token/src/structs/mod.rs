@@ -1,2 +0,0 @@
-pub mod cross_transfer;
-pub mod cross_transfer_revert;
\ No newline at end of file
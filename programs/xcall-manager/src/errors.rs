@@ -29,6 +29,10 @@ pub enum XCallManagerError {
     OnlyXcall,
     #[msg["Invalid Program"]]
     InvalidProgram,
+    #[msg["Invalid network address"]]
+    InvalidNetworkAddress,
+    #[msg("Only the program upgrade authority can initialize")]
+    OnlyUpgradeAuthority,
 }
 
 impl From<DecoderError> for XCallManagerError {
@@ -46,6 +46,29 @@ pub fn set_admin(ctx: Context<AdminAction>, new_admin: Pubkey) -> Result<()> {
     Ok(())
 }
 
+pub fn update_config(
+    ctx: Context<AdminAction>,
+    xcall: Option<Pubkey>,
+    icon_governance: Option<String>,
+) -> Result<()> {
+    let xcall_manager = &mut ctx.accounts.state;
+    if let Some(xcall) = xcall {
+        xcall_manager.xcall = xcall;
+    }
+    if let Some(icon_governance) = icon_governance {
+        require!(
+            !icon_governance.is_empty() && icon_governance.len() <= 50,
+            XCallManagerError::InvalidNetworkAddress
+        );
+        xcall_manager.icon_governance = icon_governance;
+    }
+    emit!(ConfigUpdated {
+        xcall: xcall_manager.xcall,
+        icon_governance: xcall_manager.icon_governance.clone(),
+    });
+    Ok(())
+}
+
 pub fn set_protocols(
     ctx: Context<AdminAction>,
     sources: Vec<String>,
@@ -112,54 +135,54 @@ pub fn handle_call_message<'info>(
     data: Vec<u8>,
     protocols: Vec<String>,
 ) -> Result<HandleCallMessageResponse> {
-    let mut state = ctx.accounts.state.clone();
-
-    if from != ctx.accounts.state.icon_governance {
-        return Ok(HandleCallMessageResponse {
-            success: false,
-            message: XCallManagerError::NotTheIconGovernance.to_string(),
-        });
+    // same policy as the spoke programs, only messages that can never execute are answered with
+    // `success: false` and "<error code>:<error name>", anything else fails the call for a retry
+    match process_call_message(ctx, from, data, protocols) {
+        Ok(()) => Ok(HandleCallMessageResponse {
+            success: true,
+            message: "Success".to_owned(),
+        }),
+        Err(Error::AnchorError(error)) if is_permanent_failure(error.error_code_number) => {
+            Ok(HandleCallMessageResponse {
+                success: false,
+                message: format!("{}:{}", error.error_code_number, error.error_name),
+            })
+        }
+        Err(error) => Err(error),
     }
+}
 
-    if !state.whitelisted_actions.contains(&data) {
-        return Ok(HandleCallMessageResponse {
-            success: false,
-            message: XCallManagerError::ActionNotWhitelisted.to_string(),
-        });
-    }
-    state.whitelisted_actions.retain(|a| a != &data);
+fn process_call_message<'info>(
+    ctx: Context<'_, '_, '_, 'info, HandleCallMessage<'info>>,
+    from: String,
+    data: Vec<u8>,
+    protocols: Vec<String>,
+) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    require!(from == state.icon_governance, XCallManagerError::NotTheIconGovernance);
+    require!(
+        verify_protocol_recovery(state.proposed_protocol_to_remove.clone(), &state.sources, &protocols)?,
+        XCallManagerError::ProtocolMismatch
+    );
+    require!(decode_method(&data)? == CONFIGURE_PROTOCOLS, XCallManagerError::UnknownMessageType);
+    let message = decode_handle_call_msg(&data)?;
+    // an action governance sent before the admin whitelisted it can be executed again later
+    require!(state.whitelisted_actions.contains(&data), XCallManagerError::ActionNotWhitelisted);
 
-    let verified: bool = verify_protocol_recovery(
-        ctx.accounts.state.proposed_protocol_to_remove.clone(),
-        &ctx.accounts.state.sources,
-        &protocols,
-    )?;
-    if !verified {
-        return Ok(HandleCallMessageResponse {
-            success: false,
-            message: XCallManagerError::ProtocolMismatch.to_string(),
-        });
-    }
+    state.whitelisted_actions.retain(|a| a != &data);
+    state.sources = message.sources;
+    state.destinations = message.destinations;
+    Ok(())
+}
 
-    let method = decode_method(&data)?;
-    let message = decode_handle_call_msg(&data)?;
-    if method == CONFIGURE_PROTOCOLS {
-        require!(
-            from == ctx.accounts.state.icon_governance,
-            XCallManagerError::InvalidSender
-        );
-        state.sources = message.sources;
-        state.destinations = message.destinations;
-        return Ok(HandleCallMessageResponse {
-            success: true,
-            message: "Success".to_owned(),
-        });
-    } else {
-        return Ok(HandleCallMessageResponse {
-            success: false,
-            message: XCallManagerError::UnknownMessageType.to_string(),
-        });
-    }
+fn is_permanent_failure(code: u32) -> bool {
+    [
+        u32::from(XCallManagerError::NotTheIconGovernance),
+        u32::from(XCallManagerError::ProtocolMismatch),
+        u32::from(XCallManagerError::UnknownMessageType),
+        u32::from(XCallManagerError::DecoderError),
+    ]
+    .contains(&code)
 }
 
 pub fn get_handle_call_message_accounts<'info>(
@@ -37,6 +37,15 @@ pub mod xcall_manager {
     pub fn set_admin(ctx: Context<AdminAction>, new_admin: Pubkey) -> Result<()> {
         instructions::set_admin(ctx, new_admin)
     }
+
+    pub fn update_config(
+        ctx: Context<AdminAction>,
+        xcall: Option<Pubkey>,
+        icon_governance: Option<String>,
+    ) -> Result<()> {
+        instructions::update_config(ctx, xcall, icon_governance)
+    }
+
     pub fn set_protocols(
         ctx: Context<AdminAction>,
         sources: Vec<String>,
@@ -10,6 +10,10 @@ pub struct Initialize<'info> {
     pub state: Account<'info, XmState>,
     #[account(mut)]
     pub admin: Signer<'info>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, XcallManager>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @XCallManagerError::OnlyUpgradeAuthority)]
+    pub program_data: Account<'info, ProgramData>,
     pub system_program: Program<'info, System>,
 }
 
@@ -30,6 +34,12 @@ pub struct XmState {
     pub proposed_protocol_to_remove: String,
 }
 
+#[event]
+pub struct ConfigUpdated {
+    pub xcall: Pubkey,
+    pub icon_governance: String,
+}
+
 #[derive(Accounts)]
 pub struct AdminAction<'info> {
     #[account(mut, seeds=[STATE_SEED], bump, has_one=admin)]
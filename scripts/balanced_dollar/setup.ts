@@ -3,7 +3,7 @@ import * as anchor from "@coral-xyz/anchor";
 import { PublicKey, Connection, Keypair } from "@solana/web3.js";
 import { BalancedDollar } from "../../target/types/balanced_dollar";
 import { XcallManager } from "../../target/types/xcall_manager";
-import { TransactionHelper, sleep } from "../utils";
+import { TransactionHelper, getProgramDataAddress, sleep } from "../utils";
 import { SYSTEM_PROGRAM_ID } from "@coral-xyz/anchor/dist/cjs/native/system";
 
 const balancedDollarProgram: anchor.Program<BalancedDollar> =
@@ -46,6 +46,8 @@ export class TestContext {
       .accountsStrict({
         state: BalancedDollarPDA.state().pda,
         admin: this.admin.publicKey,
+        program: balancedDollarProgram.programId,
+        programData: getProgramDataAddress(balancedDollarProgram.programId),
         systemProgram: SYSTEM_PROGRAM_ID,
       })
       .instruction();
@@ -33,3 +33,10 @@ export const uint128ToArray = (num: any) => {
 };
 
 export * from "./transaction_helper";
+
+export const getProgramDataAddress = (programId: PublicKey) => {
+  return PublicKey.findProgramAddressSync(
+    [programId.toBuffer()],
+    new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  )[0];
+};
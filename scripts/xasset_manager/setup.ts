@@ -3,7 +3,7 @@ import * as anchor from "@coral-xyz/anchor";
 import { PublicKey, Connection, Keypair } from "@solana/web3.js";
 import { AssetManager } from "../../target/types/asset_manager";
 import { XcallManager } from "../../target/types/xcall_manager";
-import { TransactionHelper, sleep } from "../utils";
+import { TransactionHelper, getProgramDataAddress, sleep } from "../utils";
 import { SYSTEM_PROGRAM_ID } from "@coral-xyz/anchor/dist/cjs/native/system";
 
 const assetManagerProgram: anchor.Program<AssetManager> =
@@ -38,7 +38,10 @@ export class TestContext {
       .initialize(xcall, icon_asset_manager, xcall_manager, xcall_manager_state)
       .accountsStrict({
         state: AssetManagerPDA.state().pda,
+        vaultNativeAccount: AssetManagerPDA.vault_native().pda,
         admin: this.admin.publicKey,
+        program: assetManagerProgram.programId,
+        programData: getProgramDataAddress(assetManagerProgram.programId),
         systemProgram: SYSTEM_PROGRAM_ID,
       })
       .instruction();
@@ -78,6 +81,33 @@ export class AssetManagerPDA {
     return { bump, pda };
   }
 
+  static token_config(mint: PublicKey) {
+    let [pda, bump] = PublicKey.findProgramAddressSync(
+      [Buffer.from("token_config"), mint.toBuffer()],
+      assetManagerProgram.programId
+    );
+
+    return { bump, pda };
+  }
+
+  static token_registry() {
+    let [pda, bump] = PublicKey.findProgramAddressSync(
+      [Buffer.from("token_registry")],
+      assetManagerProgram.programId
+    );
+
+    return { bump, pda };
+  }
+
+  static token_account_creation(mint: PublicKey) {
+    let [pda, bump] = PublicKey.findProgramAddressSync(
+      [Buffer.from("token_creation"), mint.toBuffer()],
+      assetManagerProgram.programId
+    );
+
+    return { bump, pda };
+  }
+
   static vault_native() {
     let [pda, bump] = PublicKey.findProgramAddressSync(
       [Buffer.from("vault_native")],
@@ -2,7 +2,7 @@ import * as anchor from "@coral-xyz/anchor";
 
 import { PublicKey, Connection, Keypair } from "@solana/web3.js";
 import { XcallManager } from "../../target/types/xcall_manager";
-import { TransactionHelper, sleep } from "../utils";
+import { TransactionHelper, getProgramDataAddress, sleep } from "../utils";
 import { SYSTEM_PROGRAM_ID } from "@coral-xyz/anchor/dist/cjs/native/system";
 
 const xcallManagerProgram: anchor.Program<XcallManager> =
@@ -37,6 +37,8 @@ export class TestContext {
       .accountsStrict({
         state: XcallManagerPDA.state().pda,
         admin: this.admin.publicKey,
+        program: xcallManagerProgram.programId,
+        programData: getProgramDataAddress(xcallManagerProgram.programId),
         systemProgram: SYSTEM_PROGRAM_ID,
       })
       .instruction();
@@ -69,6 +69,7 @@ describe("balanced dollar manager", () => {
   const xcall_program: anchor.Program<Xcall> = anchor.workspace.Xcall;
   let iconBnUSD = "icon/hxcnjsdkdfgj";
   let fromNid = "icon";
+  let hubRecipient = "0x1.icon/hx" + "a".repeat(40);
 
   let mint: PublicKey;
   let program_authority = BalancedDollarPDA.program_authority();
@@ -114,6 +115,85 @@ describe("balanced dollar manager", () => {
     expect(stateAccount.bnUsdToken.toString()).equals(mint.toString());
   });
 
+  it("update config", async () => {
+    let updateIx = await program.methods
+      .updateConfig(xcallProgram.programId, iconBnUSD, null, null)
+      .accountsStrict({
+        state: BalancedDollarPDA.state().pda,
+        admin: ctx.admin.publicKey,
+      })
+      .instruction();
+    let tx = await ctx.txnHelpers.buildV0Txn([updateIx], [ctx.admin]);
+    await ctx.connection.sendTransaction(tx);
+    await sleep(3);
+
+    const stateAccount = await program.account.state.fetch(
+      BalancedDollarPDA.state().pda
+    );
+    expect(stateAccount.xcall.toString()).equals(
+      xcallProgram.programId.toString()
+    );
+    expect(stateAccount.iconBnUsd).equals(iconBnUSD);
+    expect(stateAccount.xcallManagerState.toString()).equals(
+      BalancedDollarPDA.xcall_manager_state().pda.toString()
+    );
+  });
+
+  it("set and update token creation fee", async () => {
+    for (let [fee, feeMode] of [
+      [1000, { fixed: {} }],
+      [0, { disabled: {} }],
+    ] as [number, any][]) {
+      let setFeeIx = await program.methods
+        .setTokenCreationFee(new anchor.BN(fee), feeMode, new anchor.BN(0))
+        .accountsStrict({
+          tokenAccountCreationPda: BalancedDollarPDA.token_creation().pda,
+          state: BalancedDollarPDA.state().pda,
+          admin: ctx.admin.publicKey,
+          systemProgram: SYSTEM_PROGRAM_ID,
+        })
+        .instruction();
+      let tx = await ctx.txnHelpers.buildV0Txn([setFeeIx], [ctx.admin]);
+      await ctx.connection.sendTransaction(tx);
+      await sleep(3);
+
+      const feeAccount = await program.account.tokenAccountCreationFee.fetch(
+        BalancedDollarPDA.token_creation().pda
+      );
+      expect(feeAccount.tokenAccountCreationFee.toNumber()).equals(fee);
+      expect(feeAccount.feeMode).deep.equals(feeMode);
+    }
+  });
+
+  it("fund and drain fee pool", async () => {
+    const feePool = BalancedDollarPDA.fee_pool().pda;
+    const accounts = {
+      state: BalancedDollarPDA.state().pda,
+      feePool,
+      admin: ctx.admin.publicKey,
+      systemProgram: SYSTEM_PROGRAM_ID,
+    };
+
+    let fundIx = await program.methods
+      .fundFeePool(new anchor.BN(10_000_000))
+      .accountsStrict(accounts)
+      .instruction();
+    let tx = await ctx.txnHelpers.buildV0Txn([fundIx], [ctx.admin]);
+    await ctx.connection.sendTransaction(tx);
+    await sleep(3);
+    const funded = await ctx.connection.getBalance(feePool);
+    expect(funded).equals(10_000_000);
+
+    let drainIx = await program.methods
+      .drainFeePool(new anchor.BN(5_000_000))
+      .accountsStrict(accounts)
+      .instruction();
+    tx = await ctx.txnHelpers.buildV0Txn([drainIx], [ctx.admin]);
+    await ctx.connection.sendTransaction(tx);
+    await sleep(3);
+    expect(await ctx.connection.getBalance(feePool)).equals(funded - 5_000_000);
+  });
+
   it("test handle call message with uninitialized token account", async () => {
     let xcallConfig = await xcallCtx.getConfig();
 
@@ -313,7 +393,7 @@ describe("balanced dollar manager", () => {
     let nextReqId = xcallConfig.lastReqId.toNumber() + 1;
     let nextSequenceNo = xcallConfig.sequenceNo.toNumber() + 1;
     let crossTransferTx = await program.methods
-      .crossTransfer("", new anchor.BN(1000000000000000), Buffer.alloc(0))
+      .crossTransfer(hubRecipient, new anchor.BN(1000000000000000), Buffer.alloc(0))
       .accountsStrict({
         from: withdrawerTokenAccount.address,
         fromAuthority: withdrawerKeyPair.publicKey,
@@ -449,7 +529,7 @@ describe("balanced dollar manager", () => {
     let bytes = Buffer.alloc(0);
     let amount = new anchor.BN(1000000000000000);
     let crossTransferTx = await program.methods
-      .crossTransfer("", amount, bytes)
+      .crossTransfer(hubRecipient, amount, bytes)
       .accountsStrict({
         from: withdrawerTokenAccount.address,
         fromAuthority: withdrawerKeyPair.publicKey,
@@ -515,6 +595,184 @@ describe("balanced dollar manager", () => {
     expect(updatedBalance).equals(20000000000 - 1000000 + "");
   });
 
+  it("mints a transfer whose call data keeps failing once it is skipped", async () => {
+    await program.methods
+      .addAllowedProgram(SYSTEM_PROGRAM_ID)
+      .accountsStrict({
+        executionAllowlist: BalancedDollarPDA.execution_allowlist().pda,
+        state: BalancedDollarPDA.state().pda,
+        admin: ctx.admin.publicKey,
+        systemProgram: SYSTEM_PROGRAM_ID,
+      })
+      .signers([ctx.admin])
+      .rpc();
+
+    let xcallConfig = await xcallCtx.getConfig();
+    const connSn = 14;
+    let nextReqId = xcallConfig.lastReqId.toNumber() + 1;
+    let nextSequenceNo = xcallConfig.sequenceNo.toNumber() + 1;
+
+    // the system program rejects the instruction data, so the call reverts on every execution
+    const callData = rlp.encode([
+      SYSTEM_PROGRAM_ID.toString(),
+      [],
+      Buffer.from([255, 255, 255, 255]),
+    ]);
+    let recipient = Keypair.generate().publicKey;
+    const rlpEncodedData = rlp.encode([
+      "xCrossTransfer",
+      Keypair.generate().publicKey.toString(),
+      "solana/" + recipient.toString(),
+      3000000000000000000n,
+      Buffer.from(callData),
+    ]);
+    let request = new CSMessageRequest(
+      iconBnUSD,
+      program.programId.toString(),
+      nextSequenceNo,
+      MessageType.CallMessageWithRollback,
+      Buffer.from(rlpEncodedData),
+      [connectionProgram.programId.toString()]
+    );
+    let cs_message = new CSMessage(
+      CSMessageType.CSMessageRequest,
+      request.encode()
+    ).encode();
+    let recvMessageAccounts = await connectionCtx.getRecvMessageAccounts(
+      fromNid,
+      connSn,
+      nextSequenceNo,
+      cs_message,
+      CSMessageType.CSMessageRequest
+    );
+    await connectionProgram.methods
+      .recvMessage(
+        fromNid,
+        new anchor.BN(connSn),
+        Buffer.from(cs_message),
+        new anchor.BN(nextSequenceNo)
+      )
+      .accountsStrict({
+        config: ConnectionPDA.config().pda,
+        admin: ctx.admin.publicKey,
+        receipt: ConnectionPDA.receipt(fromNid, connSn).pda,
+        systemProgram: SYSTEM_PROGRAM_ID,
+        authority: ConnectionPDA.authority().pda,
+      })
+      .remainingAccounts([...recvMessageAccounts.slice(4)])
+      .signers([ctx.admin])
+      .rpc();
+    await sleep(2);
+
+    const executeCall = async () => {
+      let executeCallAccounts = await xcallCtx.getExecuteCallAccounts(
+        nextReqId,
+        Buffer.from(rlpEncodedData),
+        BalancedDollarPDA.state().pda,
+        program.programId,
+        connSn,
+        fromNid,
+        connectionProgram.programId
+      );
+      await xcallProgram.methods
+        .executeCall(
+          new anchor.BN(nextReqId),
+          fromNid,
+          new anchor.BN(connSn),
+          connectionProgram.programId,
+          Buffer.from(rlpEncodedData)
+        )
+        .accounts({
+          signer: ctx.admin.publicKey,
+          systemProgram: SYSTEM_PROGRAM_ID,
+          config: XcallPDA.config().pda,
+          admin: xcallConfig.admin,
+          proxyRequest: XcallPDA.proxyRequest(
+            fromNid,
+            connSn,
+            connectionProgram.programId
+          ).pda,
+        })
+        .remainingAccounts([...executeCallAccounts.slice(4)])
+        .signers([ctx.admin])
+        .rpc();
+      await sleep(2);
+    };
+
+    try {
+      await executeCall();
+      expect.fail("the reverting call should fail the execution");
+    } catch (err) {
+      expect(err.logs.join("\n")).to.include(SYSTEM_PROGRAM_ID.toString());
+    }
+    let recipientTokenAccount = await getAssociatedTokenAddress(mint, recipient);
+    expect(await connection.getAccountInfo(recipientTokenAccount)).equals(null);
+
+    // a stranger can not skip the call of someone else's transfer
+    let stranger = Keypair.generate();
+    await txnHelpers.airdrop(stranger.publicKey, 1000000000);
+    let rejected = false;
+    try {
+      await program.methods
+        .skipCallData(iconBnUSD, Buffer.from(rlpEncodedData))
+        .accountsStrict({
+          state: BalancedDollarPDA.state().pda,
+          messageRecord: BalancedDollarPDA.message_record(
+            iconBnUSD,
+            rlpEncodedData
+          ).pda,
+          authority: stranger.publicKey,
+          systemProgram: SYSTEM_PROGRAM_ID,
+        })
+        .signers([stranger])
+        .rpc();
+    } catch (err) {
+      rejected = true;
+      expect(err.logs.join("\n")).to.include(
+        "Only the recipient of the message or the admin can skip its call data"
+      );
+    }
+    expect(rejected).equals(true);
+
+    await program.methods
+      .skipCallData(iconBnUSD, Buffer.from(rlpEncodedData))
+      .accountsStrict({
+        state: BalancedDollarPDA.state().pda,
+        messageRecord: BalancedDollarPDA.message_record(
+          iconBnUSD,
+          rlpEncodedData
+        ).pda,
+        authority: ctx.admin.publicKey,
+        systemProgram: SYSTEM_PROGRAM_ID,
+      })
+      .signers([ctx.admin])
+      .rpc();
+    await sleep(2);
+
+    await executeCall();
+    let balance = await connection.getTokenAccountBalance(recipientTokenAccount);
+    expect(balance.value.amount).equals("3000000000");
+    const record = await program.account.messageRecord.fetch(
+      BalancedDollarPDA.message_record(iconBnUSD, rlpEncodedData).pda
+    );
+    expect(record.callSkipped).equals(true);
+    expect(record.requests.length).equals(1);
+  });
+
+  it("supply report reconciles with the mint supply", async () => {
+    let report = await program.methods
+      .getSupply()
+      .accountsStrict({
+        state: BalancedDollarPDA.state().pda,
+        mint: mint,
+      })
+      .view();
+    expect(report.burned.toString()).equals("1000000");
+    expect(
+      report.minted.add(report.reverted).sub(report.burned).toString()
+    ).equals(report.supply.toString());
+  });
+
   it("test handle force rollback complete flow with xcall", async () => {
     let xcallConfig = await xcallCtx.getConfig();
 
@@ -592,27 +850,15 @@ describe("balanced dollar manager", () => {
         systemProgram: SYSTEM_PROGRAM_ID,
         xcallAuthority: BalancedDollarPDA.xcall_authority().pda,
         signer: ctx.admin.publicKey,
+        proxyRequest: XcallPDA.proxyRequest(
+          fromNid,
+          connSn,
+          connectionProgram.programId
+        ).pda,
+        xcallConfig: XcallPDA.config().pda,
+        xcallAdmin: ctx.admin.publicKey,
       })
       .remainingAccounts([
-        {
-          pubkey: XcallPDA.proxyRequest(
-            fromNid,
-            connSn,
-            connectionProgram.programId
-          ).pda,
-          isSigner: false,
-          isWritable: true,
-        },
-        {
-          pubkey: XcallPDA.config().pda,
-          isSigner: false,
-          isWritable: true,
-        },
-        {
-          pubkey: ctx.admin.publicKey,
-          isSigner: false,
-          isWritable: true,
-        },
         //connection params
         {
           pubkey: connectionProgram.programId,
@@ -1,9 +1,10 @@
 import * as anchor from "@coral-xyz/anchor";
+import { createHash } from "crypto";
 
 import { PublicKey, Connection, Keypair } from "@solana/web3.js";
 import { BalancedDollar } from "../../target/types/balanced_dollar";
 import { XcallManager } from "../../target/types/xcall_manager";
-import { TransactionHelper, sleep } from "../utils";
+import { TransactionHelper, getProgramDataAddress, sleep } from "../utils";
 import { SYSTEM_PROGRAM_ID } from "@coral-xyz/anchor/dist/cjs/native/system";
 
 const balancedDollarProgram: anchor.Program<BalancedDollar> =
@@ -46,6 +47,8 @@ export class TestContext {
       .accountsStrict({
         state: BalancedDollarPDA.state().pda,
         admin: this.admin.publicKey,
+        program: balancedDollarProgram.programId,
+        programData: getProgramDataAddress(balancedDollarProgram.programId),
         systemProgram: SYSTEM_PROGRAM_ID,
       })
       .instruction();
@@ -76,6 +79,48 @@ export class BalancedDollarPDA {
     return { bump, pda };
   }
 
+  static token_creation() {
+    let [pda, bump] = PublicKey.findProgramAddressSync(
+      [Buffer.from("token_creation")],
+      balancedDollarProgram.programId
+    );
+
+    return { bump, pda };
+  }
+
+  static fee_pool() {
+    let [pda, bump] = PublicKey.findProgramAddressSync(
+      [Buffer.from("fee_pool")],
+      balancedDollarProgram.programId
+    );
+
+    return { bump, pda };
+  }
+
+  static execution_allowlist() {
+    let [pda, bump] = PublicKey.findProgramAddressSync(
+      [Buffer.from("execution_allowlist")],
+      balancedDollarProgram.programId
+    );
+
+    return { bump, pda };
+  }
+
+  static message_record(from: string, data: Uint8Array) {
+    let fromBytes = Buffer.from(from);
+    let fromLength = Buffer.alloc(4);
+    fromLength.writeUInt32BE(fromBytes.length);
+    let messageHash = createHash("sha256")
+      .update(Buffer.concat([fromLength, fromBytes, Buffer.from(data)]))
+      .digest();
+    let [pda, bump] = PublicKey.findProgramAddressSync(
+      [Buffer.from("message_record"), messageHash],
+      balancedDollarProgram.programId
+    );
+
+    return { bump, pda };
+  }
+
   static xcall_manager_state() {
     let [pda, bump] = PublicKey.findProgramAddressSync(
       [Buffer.from("state")],
@@ -0,0 +1,119 @@
+import * as anchor from "@coral-xyz/anchor";
+import fs from "fs";
+import { execSync } from "child_process";
+
+import { PublicKey, Connection, Keypair } from "@solana/web3.js";
+import { SpokeToken } from "../../target/types/spoke_token";
+import { XcallManager } from "../../target/types/xcall_manager";
+import { TransactionHelper, getProgramDataAddress, sleep } from "../utils";
+
+const spokeTokenProgram: anchor.Program<SpokeToken> =
+  anchor.workspace.SpokeToken;
+const xcallManagerProgram: anchor.Program<XcallManager> =
+  anchor.workspace.XcallManager;
+
+// size of the header in front of the program bytes in the program data account
+const PROGRAM_DATA_HEADER_SIZE = 45;
+
+export class TestContext {
+  admin: Keypair;
+  connection: Connection;
+  txnHelpers: TransactionHelper;
+
+  constructor(
+    connection: Connection,
+    txnHelpers: TransactionHelper,
+    admin: Keypair
+  ) {
+    this.connection = connection;
+    this.txnHelpers = txnHelpers;
+    this.admin = admin;
+  }
+
+  // the spoke token program upgrades the single mint balanced dollar deployment, which shares its
+  // program id
+  async upgradeBalancedDollar() {
+    const programId = spokeTokenProgram.programId.toString();
+    const programPath = "target/deploy/spoke_token.so";
+    const cluster = `--url ${this.connection.rpcEndpoint} --keypair ${process.env.ANCHOR_WALLET}`;
+    const programData = await this.connection.getAccountInfo(
+      getProgramDataAddress(spokeTokenProgram.programId)
+    );
+    const missing =
+      fs.statSync(programPath).size -
+      (programData.data.length - PROGRAM_DATA_HEADER_SIZE);
+    if (missing > 0) {
+      execSync(`solana program extend ${programId} ${missing} ${cluster}`);
+    }
+    execSync(
+      `solana program deploy --program-id ${programId} ${programPath} ${cluster}`
+    );
+    await sleep(3);
+  }
+}
+
+export class SpokeTokenPDA {
+  constructor() {}
+
+  static state() {
+    let [pda, bump] = PublicKey.findProgramAddressSync(
+      [Buffer.from("state")],
+      spokeTokenProgram.programId
+    );
+
+    return { bump, pda };
+  }
+
+  static mint_authority(mint: PublicKey) {
+    let [pda, bump] = PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_authority"), mint.toBuffer()],
+      spokeTokenProgram.programId
+    );
+
+    return { bump, pda };
+  }
+
+  static legacy_mint_authority() {
+    let [pda, bump] = PublicKey.findProgramAddressSync(
+      [Buffer.from("bnusd_authority")],
+      spokeTokenProgram.programId
+    );
+
+    return { bump, pda };
+  }
+
+  static token_config(mint: PublicKey) {
+    let [pda, bump] = PublicKey.findProgramAddressSync(
+      [Buffer.from("token_config"), mint.toBuffer()],
+      spokeTokenProgram.programId
+    );
+
+    return { bump, pda };
+  }
+
+  static token_creation(mint: PublicKey) {
+    let [pda, bump] = PublicKey.findProgramAddressSync(
+      [Buffer.from("token_creation"), mint.toBuffer()],
+      spokeTokenProgram.programId
+    );
+
+    return { bump, pda };
+  }
+
+  static legacy_token_creation() {
+    let [pda, bump] = PublicKey.findProgramAddressSync(
+      [Buffer.from("token_creation")],
+      spokeTokenProgram.programId
+    );
+
+    return { bump, pda };
+  }
+
+  static xcall_manager_state() {
+    let [pda, bump] = PublicKey.findProgramAddressSync(
+      [Buffer.from("state")],
+      xcallManagerProgram.programId
+    );
+    return { bump, pda };
+  }
+}
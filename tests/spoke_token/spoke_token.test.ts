@@ -0,0 +1,318 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import * as rlp from "rlp";
+import { SYSTEM_PROGRAM_ID } from "@coral-xyz/anchor/dist/cjs/native/system";
+import { expect } from "chai";
+import {
+  createMint,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getOrCreateAssociatedTokenAccount,
+} from "@solana/spl-token";
+
+import { SpokeToken } from "../../target/types/spoke_token";
+import { BalancedDollar } from "../../target/types/balanced_dollar";
+import { TransactionHelper, sleep } from "../utils/index";
+import { TestContext, SpokeTokenPDA } from "./setup";
+import { Xcall } from "../../types/xcall";
+import { CentralizedConnection } from "../../types/centralized_connection";
+import connectionIdlJson from "../../target/idl/centralized_connection.json";
+import xcallIdlJson from "../../target/idl/xcall.json";
+import { CSMessage, CSMessageType, MessageType } from "../utils/types/message";
+import { CSMessageRequest } from "../utils/types/request";
+import { TestContext as XcallContext, XcallPDA } from "../xcall/xcall/setup";
+import {
+  TestContext as ConnectionContext,
+  ConnectionPDA,
+} from "../xcall/centralized_connection/setup";
+
+const provider = anchor.AnchorProvider.env();
+anchor.setProvider(provider);
+
+const program: anchor.Program<SpokeToken> = anchor.workspace.SpokeToken;
+const balancedDollarProgram: anchor.Program<BalancedDollar> =
+  anchor.workspace.BalancedDollar;
+
+const connectionProgram: anchor.Program<CentralizedConnection> =
+  new anchor.Program(
+    connectionIdlJson as anchor.Idl,
+    provider
+  ) as unknown as anchor.Program<CentralizedConnection>;
+
+const xcallProgram: anchor.Program<Xcall> = new anchor.Program(
+  xcallIdlJson as anchor.Idl,
+  provider
+) as unknown as anchor.Program<Xcall>;
+
+describe("spoke token", () => {
+  const connection = provider.connection;
+  const wallet = provider.wallet as anchor.Wallet;
+
+  let txnHelpers = new TransactionHelper(connection, wallet.payer);
+  let ctx = new TestContext(connection, txnHelpers, wallet.payer);
+  let xcallCtx = new XcallContext(connection, txnHelpers, wallet.payer);
+  let connectionCtx = new ConnectionContext(
+    connection,
+    txnHelpers,
+    wallet.payer
+  );
+  let fromNid = "icon";
+  let hubAddress = "icon/cx" + "1".repeat(40);
+  let connSn = 200;
+
+  let legacyMint: PublicKey;
+  let legacyHubAddress: string;
+  let legacyCreationFee: bigint;
+  let mintKeyPair = Keypair.generate();
+  let mint = mintKeyPair.publicKey;
+
+  // receives a transfer of `value` hub units from `from` and executes it through xcall
+  async function receive(from: string, recipient: PublicKey, value: bigint) {
+    let xcallConfig = await xcallCtx.getConfig();
+    let nextReqId = xcallConfig.lastReqId.toNumber() + 1;
+    let nextSequenceNo = xcallConfig.sequenceNo.toNumber() + 1;
+    connSn += 1;
+
+    const rlpEncodedData = rlp.encode([
+      "xCrossTransfer",
+      Keypair.generate().publicKey.toString(),
+      "solana/" + recipient.toString(),
+      value,
+      Buffer.alloc(0),
+    ]);
+    let request = new CSMessageRequest(
+      from,
+      program.programId.toString(),
+      nextSequenceNo,
+      MessageType.CallMessageWithRollback,
+      Buffer.from(rlpEncodedData),
+      [connectionProgram.programId.toString()]
+    );
+    let cs_message = new CSMessage(
+      CSMessageType.CSMessageRequest,
+      request.encode()
+    ).encode();
+    let recvMessageAccounts = await connectionCtx.getRecvMessageAccounts(
+      fromNid,
+      connSn,
+      nextSequenceNo,
+      cs_message,
+      CSMessageType.CSMessageRequest
+    );
+    await connectionProgram.methods
+      .recvMessage(
+        fromNid,
+        new anchor.BN(connSn),
+        Buffer.from(cs_message),
+        new anchor.BN(nextSequenceNo)
+      )
+      .accountsStrict({
+        config: ConnectionPDA.config().pda,
+        admin: ctx.admin.publicKey,
+        receipt: ConnectionPDA.receipt(fromNid, connSn).pda,
+        systemProgram: SYSTEM_PROGRAM_ID,
+        authority: ConnectionPDA.authority().pda,
+      })
+      .remainingAccounts([...recvMessageAccounts.slice(4)])
+      .signers([ctx.admin])
+      .rpc();
+    await sleep(2);
+
+    let executeCallAccounts = await xcallCtx.getExecuteCallAccounts(
+      nextReqId,
+      Buffer.from(rlpEncodedData),
+      SpokeTokenPDA.state().pda,
+      program.programId,
+      connSn,
+      fromNid,
+      connectionProgram.programId
+    );
+    await xcallProgram.methods
+      .executeCall(
+        new anchor.BN(nextReqId),
+        fromNid,
+        new anchor.BN(connSn),
+        connectionProgram.programId,
+        Buffer.from(rlpEncodedData)
+      )
+      .accounts({
+        signer: ctx.admin.publicKey,
+        systemProgram: SYSTEM_PROGRAM_ID,
+        config: XcallPDA.config().pda,
+        admin: xcallConfig.admin,
+        proxyRequest: XcallPDA.proxyRequest(
+          fromNid,
+          connSn,
+          connectionProgram.programId
+        ).pda,
+      })
+      .remainingAccounts([...executeCallAccounts.slice(4)])
+      .signers([ctx.admin])
+      .rpc();
+    await sleep(2);
+  }
+
+  async function balance(mint: PublicKey, owner: PublicKey) {
+    let account = await getAccount(
+      connection,
+      getAssociatedTokenAddressSync(mint, owner)
+    ).catch(() => null);
+    return account ? account.amount : 0n;
+  }
+
+  before(async () => {
+    // the balanced dollar suite leaves a single mint deployment behind
+    const legacyState = await balancedDollarProgram.account.state.fetch(
+      SpokeTokenPDA.state().pda
+    );
+    legacyMint = legacyState.bnUsdToken;
+    legacyHubAddress = legacyState.iconBnUsd;
+    const legacyFeeAccount = await connection.getAccountInfo(
+      SpokeTokenPDA.legacy_token_creation().pda
+    );
+    legacyCreationFee = legacyFeeAccount
+      ? legacyFeeAccount.data.readBigUInt64LE(8)
+      : 0n;
+
+    await ctx.upgradeBalancedDollar();
+
+    await createMint(
+      connection,
+      wallet.payer,
+      SpokeTokenPDA.mint_authority(mint).pda,
+      null,
+      6,
+      mintKeyPair
+    );
+    for (let token of [mint, legacyMint]) {
+      await getOrCreateAssociatedTokenAccount(
+        connection,
+        wallet.payer,
+        token,
+        ctx.admin.publicKey
+      );
+    }
+  });
+
+  it("migrates the single mint deployment", async () => {
+    await program.methods
+      .migrate()
+      .accountsStrict({
+        state: SpokeTokenPDA.state().pda,
+        admin: ctx.admin.publicKey,
+        mint: legacyMint,
+        tokenConfig: SpokeTokenPDA.token_config(legacyMint).pda,
+        legacyMintAuthority: SpokeTokenPDA.legacy_mint_authority().pda,
+        legacyTokenAccountCreationPda:
+          SpokeTokenPDA.legacy_token_creation().pda,
+        tokenAccountCreationPda: SpokeTokenPDA.token_creation(legacyMint).pda,
+        systemProgram: SYSTEM_PROGRAM_ID,
+      })
+      .signers([ctx.admin])
+      .rpc();
+    await sleep(2);
+
+    const state = await program.account.state.fetch(SpokeTokenPDA.state().pda);
+    expect(state.legacyMint.toString()).equals(legacyMint.toString());
+    expect(state.tokens.length).equals(1);
+    expect(state.tokens[0].mint.toString()).equals(legacyMint.toString());
+    expect(state.tokens[0].hubAddress).equals(legacyHubAddress);
+
+    const tokenConfig = await program.account.tokenConfig.fetch(
+      SpokeTokenPDA.token_config(legacyMint).pda
+    );
+    expect(tokenConfig.mintAuthorityBump).equals(
+      SpokeTokenPDA.legacy_mint_authority().bump
+    );
+    expect(tokenConfig.hubAddress).equals(legacyHubAddress);
+
+    const creationFee = await program.account.tokenAccountCreationFee.fetch(
+      SpokeTokenPDA.token_creation(legacyMint).pda
+    );
+    expect(creationFee.tokenAccountCreationFee.toString()).equals(
+      legacyCreationFee.toString()
+    );
+    expect(
+      await connection.getAccountInfo(SpokeTokenPDA.legacy_token_creation().pda)
+    ).equals(null);
+  });
+
+  it("routes inbound transfers by hub address", async () => {
+    await program.methods
+      .registerToken(hubAddress, new anchor.BN(0), new anchor.BN(0))
+      .accountsStrict({
+        admin: ctx.admin.publicKey,
+        state: SpokeTokenPDA.state().pda,
+        mint,
+        tokenConfig: SpokeTokenPDA.token_config(mint).pda,
+        mintAuthority: SpokeTokenPDA.mint_authority(mint).pda,
+        systemProgram: SYSTEM_PROGRAM_ID,
+      })
+      .signers([ctx.admin])
+      .rpc();
+    await program.methods
+      .setTokenCreationFee(mint, new anchor.BN(0), { fixed: {} }, new anchor.BN(0))
+      .accountsStrict({
+        tokenAccountCreationPda: SpokeTokenPDA.token_creation(mint).pda,
+        state: SpokeTokenPDA.state().pda,
+        admin: ctx.admin.publicKey,
+        systemProgram: SYSTEM_PROGRAM_ID,
+      })
+      .signers([ctx.admin])
+      .rpc();
+    await sleep(2);
+
+    // 5 hub tokens are scaled down to the 6 decimals of the new mint
+    let recipient = Keypair.generate().publicKey;
+    await receive(hubAddress, recipient, 5000000000000000000n);
+    expect(await balance(mint, recipient)).equals(5000000n);
+    expect(await balance(legacyMint, recipient)).equals(0n);
+
+    // the legacy mint is still minted with the authority it was deployed with
+    let legacyRecipient = Keypair.generate().publicKey;
+    await receive(legacyHubAddress, legacyRecipient, 5000000000000000000n);
+    expect(await balance(legacyMint, legacyRecipient)).equals(5000000000n);
+    expect(await balance(mint, legacyRecipient)).equals(0n);
+  });
+
+  it("limits inbound minting per period", async () => {
+    const period = 20;
+    await program.methods
+      .updateTokenConfig(
+        mint,
+        hubAddress,
+        true,
+        new anchor.BN(period),
+        new anchor.BN(10000000)
+      )
+      .accountsStrict({
+        admin: ctx.admin.publicKey,
+        state: SpokeTokenPDA.state().pda,
+        tokenConfig: SpokeTokenPDA.token_config(mint).pda,
+      })
+      .signers([ctx.admin])
+      .rpc();
+    // the window started at registration, waiting a full period makes the next mint reset it
+    await sleep(period + 1);
+
+    let recipient = Keypair.generate().publicKey;
+    await receive(hubAddress, recipient, 10000000000000000000n);
+    expect(await balance(mint, recipient)).equals(10000000n);
+
+    try {
+      await receive(hubAddress, recipient, 1000000000000000000n);
+      expect.fail("minting above the limit should fail");
+    } catch (err) {
+      expect(err.logs.join("\n")).to.include("MintLimitExceeded");
+    }
+    expect(await balance(mint, recipient)).equals(10000000n);
+
+    await sleep(period + 1);
+    await receive(hubAddress, recipient, 1000000000000000000n);
+    expect(await balance(mint, recipient)).equals(11000000n);
+    const tokenConfig = await program.account.tokenConfig.fetch(
+      SpokeTokenPDA.token_config(mint).pda
+    );
+    expect(tokenConfig.mintedInPeriod.toString()).equals("1000000");
+  });
+});
@@ -11,6 +11,7 @@ import {
   getOrCreateAssociatedTokenAccount,
   Account,
   getAssociatedTokenAddress,
+  ASSOCIATED_TOKEN_PROGRAM_ID,
 } from "@solana/spl-token";
 
 import { TransactionHelper, sleep } from "../utils";
@@ -22,6 +23,7 @@ import { Xcall } from "../../types/xcall.js";
 import { CentralizedConnection } from "../../types/centralized_connection";
 import connectionIdlJson from "../../target/idl/centralized_connection.json";
 import xcallIdlJson from "../../target/idl/xcall.json";
+import legacyVaultMintSecret from "../fixtures/legacy_vault_mint.json";
 import { CSMessageRequest } from "../utils/types/request";
 import { CSMessageResult } from "../utils/types/result";
 import { TestContext as XcallContext, XcallPDA } from "../xcall/xcall/setup";
@@ -71,6 +73,7 @@ describe("xx asset manager test", () => {
   );
   let iconAssetManager = "icon/hxcnjsdkdfgjdjuf";
   let fromNid = "icon";
+  let hubRecipient = "0x1.icon/hx" + "a".repeat(40);
 
   let testAdmin = Keypair.generate();
   let mint: PublicKey;
@@ -78,6 +81,7 @@ describe("xx asset manager test", () => {
   let depositorKeyPair = Keypair.generate();
   let depositorTokenAccount: Account;
   let nativeDepositor = Keypair.generate();
+  const NATIVE_TOKEN = new PublicKey("11111111111111111111111111111111");
   let vaultTokenAccountPda: PublicKey;
 
   before(async () => {
@@ -118,6 +122,13 @@ describe("xx asset manager test", () => {
     expect(stateAccount.admin.toString()).equals(
       wallet.payer.publicKey.toString()
     );
+
+    const rentExemptMinimum =
+      await connection.getMinimumBalanceForRentExemption(0);
+    const vaultNativeBalance = await connection.getBalance(
+      AssetManagerPDA.vault_native().pda
+    );
+    expect(vaultNativeBalance).greaterThanOrEqual(rentExemptMinimum);
   });
 
   it("set admin test", async () => {
@@ -192,6 +203,128 @@ describe("xx asset manager test", () => {
     await sleep(3);
   });
 
+  it("register token test", async () => {
+    for (let [token, decimals] of [
+      [mint, 9],
+      [NATIVE_TOKEN, 9],
+    ] as [PublicKey, number][]) {
+      let registerIx = await program.methods
+        .registerToken(token, "0x1.icon/cx" + token.toBuffer().toString("hex").slice(0, 40), decimals)
+        .accountsStrict({
+          admin: ctx.admin.publicKey,
+          state: AssetManagerPDA.state().pda,
+          tokenConfig: AssetManagerPDA.token_config(token).pda,
+          tokenRegistry: AssetManagerPDA.token_registry().pda,
+          systemProgram: SYSTEM_PROGRAM_ID,
+        })
+        .instruction();
+      let tx = await ctx.txnHelpers.buildV0Txn([registerIx], [ctx.admin]);
+      await ctx.connection.sendTransaction(tx);
+      await sleep(3);
+    }
+
+    let tokens = await program.methods
+      .listTokens()
+      .accounts({
+        tokenRegistry: AssetManagerPDA.token_registry().pda,
+      })
+      .view();
+    expect(tokens.map((t: PublicKey) => t.toString())).to.include.members([
+      mint.toString(),
+      NATIVE_TOKEN.toString(),
+    ]);
+
+    const tokenConfig = await program.account.tokenConfig.fetch(
+      AssetManagerPDA.token_config(mint).pda
+    );
+    expect(tokenConfig.enabled).equals(true);
+    expect(tokenConfig.decimals).equals(9);
+  });
+
+  it("set hub contracts test", async () => {
+    const router = "0x1.icon/cx" + "1".repeat(40);
+    const loans = "0x1.icon/cx" + "2".repeat(40);
+    let setHubContractsIx = await program.methods
+      .setHubContracts(router, loans)
+      .accountsStrict({
+        hubContracts: AssetManagerPDA.hub_contracts().pda,
+        state: AssetManagerPDA.state().pda,
+        admin: ctx.admin.publicKey,
+        systemProgram: SYSTEM_PROGRAM_ID,
+      })
+      .instruction();
+    let tx = await ctx.txnHelpers.buildV0Txn([setHubContractsIx], [ctx.admin]);
+    await ctx.connection.sendTransaction(tx);
+    await sleep(3);
+
+    const hubContracts = await program.account.hubContracts.fetch(
+      AssetManagerPDA.hub_contracts().pda
+    );
+    expect(hubContracts.router).equals(router);
+    expect(hubContracts.loans).equals(loans);
+  });
+
+  it("register token vault test", async () => {
+    let vaultMint = await createMint(
+      provider.connection,
+      wallet.payer,
+      wallet.payer.publicKey,
+      null,
+      6
+    );
+    let registerIx = await program.methods
+      .registerToken(vaultMint, "0x1.icon/cx" + "b".repeat(40), 6)
+      .accountsStrict({
+        admin: ctx.admin.publicKey,
+        state: AssetManagerPDA.state().pda,
+        tokenConfig: AssetManagerPDA.token_config(vaultMint).pda,
+        tokenRegistry: AssetManagerPDA.token_registry().pda,
+        systemProgram: SYSTEM_PROGRAM_ID,
+      })
+      .instruction();
+    let vaultTokenAddress = await getAssociatedTokenAddress(
+      vaultMint,
+      AssetManagerPDA.vault(vaultMint).pda,
+      true
+    );
+    let registerVaultIx = await program.methods
+      .registerTokenVault(bn(300), bn(900), bn(100), false)
+      .accountsStrict({
+        admin: ctx.admin.publicKey,
+        state: AssetManagerPDA.state().pda,
+        mint: vaultMint,
+        tokenConfig: AssetManagerPDA.token_config(vaultMint).pda,
+        valultAuthority: AssetManagerPDA.vault(vaultMint).pda,
+        vaultTokenAccount: vaultTokenAddress,
+        tokenState: AssetManagerPDA.token_state(vaultMint).pda,
+        tokenAccountCreationPda:
+          AssetManagerPDA.token_account_creation(vaultMint).pda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SYSTEM_PROGRAM_ID,
+      })
+      .instruction();
+    let tx = await ctx.txnHelpers.buildV0Txn(
+      [registerIx, registerVaultIx],
+      [ctx.admin]
+    );
+    await ctx.connection.sendTransaction(tx);
+    await sleep(3);
+
+    let vaultBalance = await connection.getTokenAccountBalance(
+      vaultTokenAddress
+    );
+    expect(vaultBalance.value.amount).equals("0");
+    const tokenState = await program.account.tokenState.fetch(
+      AssetManagerPDA.token_state(vaultMint).pda
+    );
+    expect(tokenState.percentage.toNumber()).equals(900);
+    const creationFee = await program.account.tokenAccountCreationFee.fetch(
+      AssetManagerPDA.token_account_creation(vaultMint).pda
+    );
+    expect(creationFee.tokenAccountCreationFee.toNumber()).equals(100);
+  });
+
   it("deposit token", async () => {
     let { pda } = XcallPDA.config();
     let xcall_config = await xcall_program.account.config.fetch(pda);
@@ -225,12 +358,14 @@ describe("xx asset manager test", () => {
     let depositTokenIx = await program.methods
       .depositToken(
         bn(1000000000),
-        depositorTokenAccount.address.toString(),
+        hubRecipient,
         bytes
       )
       .accountsStrict({
         from: depositorTokenAccount.address,
         vaultNativeAccount: null,
+        wsolUnwrapAccount: null,
+        nativeMint: null,
         fromAuthority: depositorKeyPair.publicKey,
         vaultTokenAccount: vaultTokenAccount.address,
         valultAuthority: vaultTokenAccountPda,
@@ -242,6 +377,10 @@ describe("xx asset manager test", () => {
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SYSTEM_PROGRAM_ID,
         xcallAuthority: AssetManagerPDA.xcall_authority().pda,
+        tokenConfig: AssetManagerPDA.token_config(mint).pda,
+        tokenState: AssetManagerPDA.token_state(mint).pda,
+        hubContracts: null,
+        depositRecord: AssetManagerPDA.deposit_record(xcall_config.sequenceNo.toNumber() + 1).pda,
       })
       .remainingAccounts([
         {
@@ -309,6 +448,351 @@ describe("xx asset manager test", () => {
     expect(withdraw_limit.toNumber()).greaterThan(0);
   });
 
+  it("get vault accounting test", async () => {
+    let accounting = await program.methods
+      .getVaultAccounting()
+      .accountsStrict({
+        tokenState: AssetManagerPDA.token_state(mint).pda,
+        vaultTokenAccount: vaultTokenAccount.address,
+        valultAuthority: AssetManagerPDA.vault(mint).pda,
+      })
+      .view();
+
+    expect(accounting.deposited.toString()).equals("1000000000");
+    expect(accounting.vaultBalance.toString()).equals("1000000000");
+    expect(accounting.delta.toNumber()).equals(0);
+  });
+
+  it("migrate grows a legacy token state and keeps its vault accounting", async () => {
+    // tests/fixtures/legacy_token_state.json is the token state of this mint as created before
+    // the vault counters existed, its vault already holds funds when the program is upgraded
+    let legacyMint = Keypair.fromSecretKey(Uint8Array.from(legacyVaultMintSecret)).publicKey;
+    await createMint(
+      provider.connection,
+      wallet.payer,
+      wallet.payer.publicKey,
+      null,
+      9,
+      Keypair.fromSecretKey(Uint8Array.from(legacyVaultMintSecret))
+    );
+    let registerIx = await program.methods
+      .registerToken(legacyMint, "0x1.icon/cx" + "c".repeat(40), 9)
+      .accountsStrict({
+        admin: ctx.admin.publicKey,
+        state: AssetManagerPDA.state().pda,
+        tokenConfig: AssetManagerPDA.token_config(legacyMint).pda,
+        tokenRegistry: AssetManagerPDA.token_registry().pda,
+        systemProgram: SYSTEM_PROGRAM_ID,
+      })
+      .instruction();
+    let tx = await ctx.txnHelpers.buildV0Txn([registerIx], [ctx.admin]);
+    await ctx.connection.sendTransaction(tx);
+    await sleep(3);
+
+    let legacyVault = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      wallet.payer,
+      legacyMint,
+      AssetManagerPDA.vault(legacyMint).pda,
+      true
+    );
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      legacyMint,
+      legacyVault.address,
+      wallet.payer,
+      5000000000
+    );
+    let legacyTokenState = AssetManagerPDA.token_state(legacyMint).pda;
+    expect((await connection.getAccountInfo(legacyTokenState)).data.length).equals(72);
+
+    await program.methods
+      .migrate()
+      .accountsStrict({
+        state: AssetManagerPDA.state().pda,
+        admin: ctx.admin.publicKey,
+        systemProgram: SYSTEM_PROGRAM_ID,
+      })
+      .remainingAccounts([
+        { pubkey: legacyTokenState, isSigner: false, isWritable: true },
+        { pubkey: legacyVault.address, isSigner: false, isWritable: false },
+      ])
+      .signers([ctx.admin])
+      .rpc();
+    await sleep(2);
+
+    const getAccounting = () =>
+      program.methods
+        .getVaultAccounting()
+        .accountsStrict({
+          tokenState: legacyTokenState,
+          vaultTokenAccount: legacyVault.address,
+          valultAuthority: AssetManagerPDA.vault(legacyMint).pda,
+        })
+        .view();
+    let accounting = await getAccounting();
+    expect(accounting.deposited.toString()).equals("5000000000");
+    expect(accounting.withdrawn.toString()).equals("0");
+    expect(accounting.reverted.toString()).equals("0");
+    expect(accounting.delta.toNumber()).equals(0);
+
+    let xcall_config = await xcall_program.account.config.fetch(XcallPDA.config().pda);
+    let sn = xcall_config.sequenceNo.toNumber() + 1;
+    let legacyDepositor = Keypair.generate();
+    await txnHelpers.airdrop(legacyDepositor.publicKey, 5000000000);
+    let legacyDepositorTokenAccount = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      wallet.payer,
+      legacyMint,
+      legacyDepositor.publicKey
+    );
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      legacyMint,
+      legacyDepositorTokenAccount.address,
+      wallet.payer,
+      1000000000
+    );
+    await sleep(3);
+
+    let depositTokenIx = await program.methods
+      .depositToken(bn(1000000000), hubRecipient, Buffer.alloc(0))
+      .accountsStrict({
+        from: legacyDepositorTokenAccount.address,
+        vaultNativeAccount: null,
+        wsolUnwrapAccount: null,
+        nativeMint: null,
+        fromAuthority: legacyDepositor.publicKey,
+        vaultTokenAccount: legacyVault.address,
+        valultAuthority: AssetManagerPDA.vault(legacyMint).pda,
+        state: AssetManagerPDA.state().pda,
+        xcallManagerState: AssetManagerPDA.xcall_manager_state().pda,
+        xcallConfig: XcallPDA.config().pda,
+        xcall: xcall_program.programId,
+        xcallManager: xcall_manager_program.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SYSTEM_PROGRAM_ID,
+        xcallAuthority: AssetManagerPDA.xcall_authority().pda,
+        tokenConfig: AssetManagerPDA.token_config(legacyMint).pda,
+        tokenState: legacyTokenState,
+        hubContracts: null,
+        depositRecord: AssetManagerPDA.deposit_record(sn).pda,
+      })
+      .remainingAccounts([
+        { pubkey: XcallPDA.config().pda, isSigner: false, isWritable: true },
+        { pubkey: XcallPDA.rollback(sn).pda, isSigner: false, isWritable: true },
+        {
+          pubkey: new PublicKey("Sysvar1nstructions1111111111111111111111111"),
+          isSigner: false,
+          isWritable: false,
+        },
+        { pubkey: xcall_config.feeHandler, isSigner: false, isWritable: true },
+        { pubkey: connectionProgram.programId, isSigner: false, isWritable: true },
+        { pubkey: ConnectionPDA.config().pda, isSigner: false, isWritable: true },
+        { pubkey: ConnectionPDA.network_fee(fromNid).pda, isSigner: false, isWritable: true },
+      ])
+      .instruction();
+    tx = await ctx.txnHelpers.buildV0Txn(
+      [ComputeBudgetProgram.setComputeUnitLimit({ units: 1000000 }), depositTokenIx],
+      [legacyDepositor]
+    );
+    await ctx.connection.sendTransaction(tx);
+    await sleep(2);
+
+    accounting = await getAccounting();
+    expect(accounting.deposited.toString()).equals("6000000000");
+    expect(accounting.vaultBalance.toString()).equals("6000000000");
+    expect(accounting.delta.toNumber()).equals(0);
+  });
+
+  it("deposit token without recipient is credited to the depositor", async () => {
+    let { pda } = XcallPDA.config();
+    let xcall_config = await xcall_program.account.config.fetch(pda);
+    let sn = xcall_config.sequenceNo.toNumber() + 1;
+
+    let bytes = Buffer.alloc(0);
+    let depositTokenIx = await program.methods
+      .depositToken(
+        bn(1000),
+        null,
+        bytes
+      )
+      .accountsStrict({
+        from: depositorTokenAccount.address,
+        vaultNativeAccount: null,
+        wsolUnwrapAccount: null,
+        nativeMint: null,
+        fromAuthority: depositorKeyPair.publicKey,
+        vaultTokenAccount: vaultTokenAccount.address,
+        valultAuthority: vaultTokenAccountPda,
+        state: AssetManagerPDA.state().pda,
+        xcallManagerState: AssetManagerPDA.xcall_manager_state().pda,
+        xcallConfig: XcallPDA.config().pda,
+        xcall: xcall_program.programId,
+        xcallManager: xcall_manager_program.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SYSTEM_PROGRAM_ID,
+        xcallAuthority: AssetManagerPDA.xcall_authority().pda,
+        tokenConfig: AssetManagerPDA.token_config(mint).pda,
+        tokenState: AssetManagerPDA.token_state(mint).pda,
+        hubContracts: null,
+        depositRecord: AssetManagerPDA.deposit_record(xcall_config.sequenceNo.toNumber() + 1).pda,
+      })
+      .remainingAccounts([
+        {
+          pubkey: XcallPDA.config().pda,
+          isSigner: false,
+          isWritable: true,
+        },
+        {
+          pubkey: XcallPDA.rollback(xcall_config.sequenceNo.toNumber() + 1).pda,
+          isSigner: false,
+          isWritable: true,
+        },
+        {
+          pubkey: new PublicKey("Sysvar1nstructions1111111111111111111111111"),
+          isSigner: false,
+          isWritable: false,
+        },
+        {
+          pubkey: xcall_config.feeHandler,
+          isSigner: false,
+          isWritable: true,
+        },
+        //connection params
+        {
+          pubkey: connectionProgram.programId,
+          isSigner: false,
+          isWritable: true,
+        },
+        {
+          pubkey: ConnectionPDA.config().pda,
+          isSigner: false,
+          isWritable: true,
+        },
+        {
+          pubkey: ConnectionPDA.network_fee(fromNid).pda,
+          isSigner: false,
+          isWritable: true,
+        },
+      ])
+      .instruction();
+    const modifyComputeUnits = ComputeBudgetProgram.setComputeUnitLimit({
+      units: 1000000,
+    });
+
+    const addPriorityFee = ComputeBudgetProgram.setComputeUnitPrice({
+      microLamports: 0,
+    });
+    let tx = await ctx.txnHelpers.buildV0Txn(
+      [modifyComputeUnits, addPriorityFee, depositTokenIx],
+      [depositorKeyPair]
+    );
+    await ctx.connection.sendTransaction(tx);
+    await sleep(2);
+
+    let depositRecord = await program.account.depositRecord.fetch(
+      AssetManagerPDA.deposit_record(sn).pda
+    );
+    expect(depositRecord.to).equals(
+      `${xcall_config.networkId}/${depositorKeyPair.publicKey.toString()}`
+    );
+    expect(depositRecord.from.toString()).equals(depositorKeyPair.publicKey.toString());
+    expect(depositRecord.amount.toString()).equals("1000");
+  });
+
+  it("deposit token is sent through the xcall set in the state", async () => {
+    let updateConfig = (xcall: PublicKey) =>
+      program.methods
+        .updateConfig(xcall, null, null, null)
+        .accountsStrict({
+          state: AssetManagerPDA.state().pda,
+          admin: ctx.admin.publicKey,
+        })
+        .signers([ctx.admin])
+        .rpc();
+    let deposit = async (xcall: PublicKey) => {
+      let xcall_config = await xcall_program.account.config.fetch(
+        XcallPDA.config().pda
+      );
+      return program.methods
+        .depositToken(bn(1000), hubRecipient, Buffer.alloc(0))
+        .accountsStrict({
+          from: depositorTokenAccount.address,
+          vaultNativeAccount: null,
+          wsolUnwrapAccount: null,
+          nativeMint: null,
+          fromAuthority: depositorKeyPair.publicKey,
+          vaultTokenAccount: vaultTokenAccount.address,
+          valultAuthority: vaultTokenAccountPda,
+          state: AssetManagerPDA.state().pda,
+          xcallManagerState: AssetManagerPDA.xcall_manager_state().pda,
+          xcallConfig: XcallPDA.config().pda,
+          xcall,
+          xcallManager: xcall_manager_program.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SYSTEM_PROGRAM_ID,
+          xcallAuthority: AssetManagerPDA.xcall_authority().pda,
+          tokenConfig: AssetManagerPDA.token_config(mint).pda,
+          tokenState: AssetManagerPDA.token_state(mint).pda,
+          hubContracts: null,
+          depositRecord: null,
+        })
+        .remainingAccounts([
+          { pubkey: XcallPDA.config().pda, isSigner: false, isWritable: true },
+          {
+            pubkey: XcallPDA.rollback(xcall_config.sequenceNo.toNumber() + 1).pda,
+            isSigner: false,
+            isWritable: true,
+          },
+          {
+            pubkey: new PublicKey("Sysvar1nstructions1111111111111111111111111"),
+            isSigner: false,
+            isWritable: false,
+          },
+          { pubkey: xcall_config.feeHandler, isSigner: false, isWritable: true },
+          { pubkey: connectionProgram.programId, isSigner: false, isWritable: true },
+          { pubkey: ConnectionPDA.config().pda, isSigner: false, isWritable: true },
+          {
+            pubkey: ConnectionPDA.network_fee(fromNid).pda,
+            isSigner: false,
+            isWritable: true,
+          },
+        ])
+        .preInstructions([
+          ComputeBudgetProgram.setComputeUnitLimit({ units: 1000000 }),
+        ])
+        .signers([depositorKeyPair])
+        .rpc();
+    };
+
+    // the xcall program and config are checked against the state, not the xcall the asset
+    // manager was built against
+    await updateConfig(connectionProgram.programId);
+    try {
+      await deposit(xcall_program.programId);
+      expect.fail("deposit through the previous xcall should fail");
+    } catch (err) {
+      expect(err.error.errorCode.code).equals("InvalidXcallProgram");
+    }
+    try {
+      await deposit(connectionProgram.programId);
+      expect.fail("deposit with a config of another xcall should fail");
+    } catch (err) {
+      expect(err.error.errorCode.code).equals("InvalidXcallConfig");
+    }
+
+    await updateConfig(xcall_program.programId);
+    let sequenceNo = (await xcallCtx.getConfig()).sequenceNo.toNumber();
+    await deposit(xcall_program.programId);
+    await sleep(2);
+    expect((await xcallCtx.getConfig()).sequenceNo.toNumber()).equals(
+      sequenceNo + 1
+    );
+  });
+
   it("deposit native token", async () => {
     let { pda } = XcallPDA.config();
     let xcall_config = await xcall_program.account.config.fetch(pda);
@@ -324,7 +808,7 @@ describe("xx asset manager test", () => {
     let depositTokenIx = await program.methods
       .depositNative(
         bn(1000000000),
-        nativeDepositor.publicKey.toString(),
+        hubRecipient,
         bytes
       )
       .accountsStrict({
@@ -333,6 +817,8 @@ describe("xx asset manager test", () => {
         vaultTokenAccount: null,
         valultAuthority: null,
         vaultNativeAccount: AssetManagerPDA.vault_native().pda,
+        wsolUnwrapAccount: null,
+        nativeMint: null,
         state: AssetManagerPDA.state().pda,
         xcallManagerState: AssetManagerPDA.xcall_manager_state().pda,
         xcallConfig: XcallPDA.config().pda,
@@ -341,6 +827,10 @@ describe("xx asset manager test", () => {
         tokenProgram: null,
         systemProgram: SYSTEM_PROGRAM_ID,
         xcallAuthority: AssetManagerPDA.xcall_authority().pda,
+        tokenConfig: AssetManagerPDA.token_config(NATIVE_TOKEN).pda,
+        tokenState: AssetManagerPDA.token_state(NATIVE_TOKEN).pda,
+        hubContracts: null,
+        depositRecord: AssetManagerPDA.deposit_record(xcall_config.sequenceNo.toNumber() + 1).pda,
       })
       .remainingAccounts([
         {
@@ -501,6 +991,50 @@ describe("xx asset manager test", () => {
       let vaultTokenBalanceAfter = await connection.getTokenAccountBalance(vaultTokenAccount.address);
       let expectedVaultBalance = Number(vaultTokenBalanceBefore.value.amount) - transferAmount
       expect(vaultTokenBalanceAfter.value.amount).equals(expectedVaultBalance.toString())
+
+      let messageRecord = AssetManagerPDA.message_record(iconAssetManager, rlpEncodedData).pda;
+      let record = await program.account.messageRecord.fetch(messageRecord);
+      let proxyRequest = XcallPDA.proxyRequest(fromNid, connSn, connectionProgram.programId).pda;
+      expect(record.requests.map((request) => request.toString())).deep.equals([
+        proxyRequest.toString(),
+      ]);
+
+      // the record can only be closed with its executed requests, which xcall has closed
+      let rejected = false;
+      try {
+        await program.methods
+          .closeMessageRecord()
+          .accountsStrict({
+            state: AssetManagerPDA.state().pda,
+            admin: ctx.admin.publicKey,
+            messageRecord,
+          })
+          .remainingAccounts([
+            { pubkey: XcallPDA.config().pda, isSigner: false, isWritable: false },
+          ])
+          .signers([ctx.admin])
+          .rpc();
+      } catch (err) {
+        rejected = true;
+        expect(err.logs.join("\n")).to.include(
+          "The xcall requests of the message record are not closed"
+        );
+      }
+      expect(rejected).equals(true);
+
+      expect(await connection.getAccountInfo(proxyRequest)).equals(null);
+      await program.methods
+        .closeMessageRecord()
+        .accountsStrict({
+          state: AssetManagerPDA.state().pda,
+          admin: ctx.admin.publicKey,
+          messageRecord,
+        })
+        .remainingAccounts([{ pubkey: proxyRequest, isSigner: false, isWritable: false }])
+        .signers([ctx.admin])
+        .rpc();
+      await sleep(2);
+      expect(await connection.getAccountInfo(messageRecord)).equals(null);
   });
 
   it("test handle call message rollback complete flow with xcall", async () => {
@@ -512,12 +1046,14 @@ describe("xx asset manager test", () => {
     let depositTokenIx = await program.methods
       .depositToken(
         bn(1000000000),
-        depositorTokenAccount.address.toString(),
+        hubRecipient,
         bytes
       )
       .accountsStrict({
         from: depositorTokenAccount.address,
         vaultNativeAccount: null,
+        wsolUnwrapAccount: null,
+        nativeMint: null,
         fromAuthority: depositorKeyPair.publicKey,
         vaultTokenAccount: vaultTokenAccount.address,
         valultAuthority: vaultTokenAccountPda,
@@ -529,6 +1065,10 @@ describe("xx asset manager test", () => {
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SYSTEM_PROGRAM_ID,
         xcallAuthority: AssetManagerPDA.xcall_authority().pda,
+        tokenConfig: AssetManagerPDA.token_config(mint).pda,
+        tokenState: AssetManagerPDA.token_state(mint).pda,
+        hubContracts: null,
+        depositRecord: AssetManagerPDA.deposit_record(xcall_sequence_no).pda,
       })
       .remainingAccounts([
         {
@@ -644,6 +1184,12 @@ describe("xx asset manager test", () => {
       .signers([ctx.admin])
       .rpc();
     await sleep(3);
+
+    const depositRecord = await program.account.depositRecord.fetch(
+      AssetManagerPDA.deposit_record(xcall_sequence_no).pda
+    );
+    expect(depositRecord.sn.toNumber()).equals(xcall_sequence_no);
+    expect(depositRecord.status).to.have.property("reverted");
   });
 
   it("test handle call message for native token complete flow with xcall", async () => {
@@ -733,6 +1279,8 @@ describe("xx asset manager test", () => {
         ).pda,
       })
       .remainingAccounts([...executeCallAccounts.slice(4)])
+      // the request is read from the executing top level instruction, not the first one
+      .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 1_000_000 })])
       .signers([ctx.admin])
       .rpc();
     await sleep(2);
@@ -748,7 +1296,7 @@ describe("xx asset manager test", () => {
     let depositTokenIx = await program.methods
       .depositNative(
         bn(1000000000),
-        nativeDepositor.publicKey.toString(),
+        hubRecipient,
         bytes
       )
       .accountsStrict({
@@ -757,6 +1305,8 @@ describe("xx asset manager test", () => {
         vaultTokenAccount: null,
         valultAuthority: null,
         vaultNativeAccount: AssetManagerPDA.vault_native().pda,
+        wsolUnwrapAccount: null,
+        nativeMint: null,
         state: AssetManagerPDA.state().pda,
         xcallManagerState: AssetManagerPDA.xcall_manager_state().pda,
         xcallConfig: XcallPDA.config().pda,
@@ -765,6 +1315,10 @@ describe("xx asset manager test", () => {
         tokenProgram: null,
         systemProgram: SYSTEM_PROGRAM_ID,
         xcallAuthority: AssetManagerPDA.xcall_authority().pda,
+        tokenConfig: AssetManagerPDA.token_config(NATIVE_TOKEN).pda,
+        tokenState: AssetManagerPDA.token_state(NATIVE_TOKEN).pda,
+        hubContracts: null,
+        depositRecord: AssetManagerPDA.deposit_record(nextSequenceNo).pda,
       })
       .remainingAccounts([
         {
@@ -954,27 +1508,15 @@ describe("xx asset manager test", () => {
         systemProgram: SYSTEM_PROGRAM_ID,
         xcallAuthority: AssetManagerPDA.xcall_authority().pda,
         signer: ctx.admin.publicKey,
+        proxyRequest: XcallPDA.proxyRequest(
+          fromNid,
+          connSn,
+          connectionProgram.programId
+        ).pda,
+        xcallConfig: XcallPDA.config().pda,
+        xcallAdmin: ctx.admin.publicKey,
       })
       .remainingAccounts([
-        {
-          pubkey: XcallPDA.proxyRequest(
-            fromNid,
-            connSn,
-            connectionProgram.programId
-          ).pda,
-          isSigner: false,
-          isWritable: true,
-        },
-        {
-          pubkey: XcallPDA.config().pda,
-          isSigner: false,
-          isWritable: true,
-        },
-        {
-          pubkey: ctx.admin.publicKey,
-          isSigner: false,
-          isWritable: true,
-        },
         //connection params
         {
           pubkey: connectionProgram.programId,
@@ -1007,4 +1549,101 @@ describe("xx asset manager test", () => {
     await ctx.connection.sendTransaction(tx);
     await sleep(2);
   });
+
+  it("only the depositor can request the rollback of a stuck withdrawal", async () => {
+    let xcallConfig = await xcallCtx.getConfig();
+
+    const connSn = 13;
+    let nextSequenceNo = xcallConfig.sequenceNo.toNumber() + 1;
+
+    const rlpEncodedData = rlp.encode([
+      "WithdrawTo",
+      mint.toString(),
+      depositorKeyPair.publicKey.toString(),
+      100000000,
+    ]);
+    let request = new CSMessageRequest(
+      iconAssetManager,
+      program.programId.toString(),
+      nextSequenceNo,
+      MessageType.CallMessageWithRollback,
+      Buffer.from(rlpEncodedData),
+      [connectionProgram.programId.toString()]
+    );
+    let cs_message = new CSMessage(
+      CSMessageType.CSMessageRequest,
+      request.encode()
+    ).encode();
+    let recvMessageAccounts = await connectionCtx.getRecvMessageAccounts(
+      fromNid,
+      connSn,
+      nextSequenceNo,
+      cs_message,
+      CSMessageType.CSMessageRequest
+    );
+    await connectionProgram.methods
+      .recvMessage(
+        fromNid,
+        new anchor.BN(connSn),
+        Buffer.from(cs_message),
+        new anchor.BN(nextSequenceNo)
+      )
+      .accountsStrict({
+        config: ConnectionPDA.config().pda,
+        admin: ctx.admin.publicKey,
+        receipt: ConnectionPDA.receipt(fromNid, connSn).pda,
+        systemProgram: SYSTEM_PROGRAM_ID,
+        authority: ConnectionPDA.authority().pda,
+      })
+      .remainingAccounts([...recvMessageAccounts.slice(4)])
+      .signers([ctx.admin])
+      .rpc();
+    await sleep(2);
+
+    let depositRecord = (await program.account.depositRecord.all()).find(
+      (record) =>
+        record.account.from.equals(depositorKeyPair.publicKey) &&
+        record.account.token.equals(mint)
+    );
+    let proxyRequest = XcallPDA.proxyRequest(
+      fromNid,
+      connSn,
+      connectionProgram.programId
+    ).pda;
+    const requestRollback = (from: Keypair) =>
+      program.methods
+        .requestUserRollback(fromNid, new anchor.BN(connSn), connectionProgram.programId)
+        .accountsStrict({
+          state: AssetManagerPDA.state().pda,
+          requester: ctx.admin.publicKey,
+          from: from.publicKey,
+          depositRecord: depositRecord.publicKey,
+          proxyRequest,
+          rollbackRequest: AssetManagerPDA.rollback_request(proxyRequest).pda,
+          systemProgram: SYSTEM_PROGRAM_ID,
+        })
+        .signers([ctx.admin, from])
+        .rpc();
+
+    let rejected = false;
+    try {
+      await requestRollback(Keypair.generate());
+    } catch (err) {
+      rejected = true;
+      expect(err.error.errorCode.code).equals("InvalidDepositRecord");
+    }
+    expect(rejected).equals(true);
+
+    await requestRollback(depositorKeyPair);
+    await sleep(2);
+    const rollbackRequest = await program.account.rollbackRequest.fetch(
+      AssetManagerPDA.rollback_request(proxyRequest).pda
+    );
+    expect(rollbackRequest.requester.toString()).equals(
+      ctx.admin.publicKey.toString()
+    );
+    expect(rollbackRequest.proxyRequest.toString()).equals(
+      proxyRequest.toString()
+    );
+  });
 });
@@ -1,9 +1,15 @@
 import * as anchor from "@coral-xyz/anchor";
+import { createHash } from "crypto";
 
 import { PublicKey, Connection, Keypair } from "@solana/web3.js";
 import { AssetManager } from "../../target/types/asset_manager";
 import { XcallManager } from "../../target/types/xcall_manager";
-import { TransactionHelper, sleep } from "../utils";
+import {
+  TransactionHelper,
+  getProgramDataAddress,
+  sleep,
+  uint128ToArray,
+} from "../utils";
 import { SYSTEM_PROGRAM_ID } from "@coral-xyz/anchor/dist/cjs/native/system";
 
 const assetManagerProgram: anchor.Program<AssetManager> =
@@ -38,7 +44,10 @@ export class TestContext {
       .initialize(xcall, icon_asset_manager, xcall_manager, xcall_manager_state)
       .accountsStrict({
         state: AssetManagerPDA.state().pda,
+        vaultNativeAccount: AssetManagerPDA.vault_native().pda,
         admin: this.admin.publicKey,
+        program: assetManagerProgram.programId,
+        programData: getProgramDataAddress(assetManagerProgram.programId),
         systemProgram: SYSTEM_PROGRAM_ID,
       })
       .instruction();
@@ -78,6 +87,75 @@ export class AssetManagerPDA {
     return { bump, pda };
   }
 
+  static deposit_record(sequenceNo: number) {
+    let [pda, bump] = PublicKey.findProgramAddressSync(
+      [Buffer.from("deposit_record"), uint128ToArray(sequenceNo)],
+      assetManagerProgram.programId
+    );
+
+    return { bump, pda };
+  }
+
+  static rollback_request(proxyRequest: PublicKey) {
+    let [pda, bump] = PublicKey.findProgramAddressSync(
+      [Buffer.from("rollback_request"), proxyRequest.toBuffer()],
+      assetManagerProgram.programId
+    );
+
+    return { bump, pda };
+  }
+
+  static message_record(from: string, data: Uint8Array) {
+    let fromBytes = Buffer.from(from);
+    let fromLength = Buffer.alloc(4);
+    fromLength.writeUInt32BE(fromBytes.length);
+    let messageHash = createHash("sha256")
+      .update(Buffer.concat([fromLength, fromBytes, Buffer.from(data)]))
+      .digest();
+    let [pda, bump] = PublicKey.findProgramAddressSync(
+      [Buffer.from("message_record"), messageHash],
+      assetManagerProgram.programId
+    );
+
+    return { bump, pda };
+  }
+
+  static hub_contracts() {
+    let [pda, bump] = PublicKey.findProgramAddressSync(
+      [Buffer.from("hub_contracts")],
+      assetManagerProgram.programId
+    );
+
+    return { bump, pda };
+  }
+
+  static token_config(mint: PublicKey) {
+    let [pda, bump] = PublicKey.findProgramAddressSync(
+      [Buffer.from("token_config"), mint.toBuffer()],
+      assetManagerProgram.programId
+    );
+
+    return { bump, pda };
+  }
+
+  static token_registry() {
+    let [pda, bump] = PublicKey.findProgramAddressSync(
+      [Buffer.from("token_registry")],
+      assetManagerProgram.programId
+    );
+
+    return { bump, pda };
+  }
+
+  static token_account_creation(mint: PublicKey) {
+    let [pda, bump] = PublicKey.findProgramAddressSync(
+      [Buffer.from("token_creation"), mint.toBuffer()],
+      assetManagerProgram.programId
+    );
+
+    return { bump, pda };
+  }
+
   static vault_native() {
     let [pda, bump] = PublicKey.findProgramAddressSync(
       [Buffer.from("vault_native")],